@@ -1,13 +1,29 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader,};
+use std::io::{BufRead, BufReader, BufWriter};
+use crate::trajectory::Trajectory;
+
+mod solver;
+mod trajectory;
 
 fn main() {
+    // --export writes the trajectories as CSV and SVG files in the current directory
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let export = args.iter().any(|arg| arg == "--export");
+    let targets: Vec<&String> = args.iter().filter(|&arg| arg != "--export").collect();
     println!("{:-<32}", "Part 1 ");
-    part1();
+    part1(export);
     println!("{:-<32}", "Part 2 ");
-    part2();
+    part2(export);
+    // Optionally look for the smallest fix that makes the run end at the given target
+    if targets.len() == 2 {
+        let x = targets[0].parse().expect("Invalid target x");
+        let y = targets[1].parse().expect("Invalid target depth");
+        println!("{:-<32}", "Repair ");
+        repair(&Pos::new(x, y));
+    }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
 struct Pos {
     x: i64,
     y: i64,
@@ -19,6 +35,7 @@ impl Pos {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
 enum Command {
     Forward(i64),
     Down(i64),
@@ -37,44 +54,139 @@ impl Command {
             _         => Err("Invalid command name")
         }
     }
+
+    fn units(&self) -> i64 {
+        match *self {
+            Command::Forward(units) | Command::Down(units) | Command::Up(units) => units,
+        }
+    }
+
+    fn with_units(&self, units: i64) -> Command {
+        match self {
+            Command::Forward(_) => Command::Forward(units),
+            Command::Down(_)    => Command::Down(units),
+            Command::Up(_)      => Command::Up(units),
+        }
+    }
 }
 
-fn part1() {
-    let mut pos = Pos::new(0, 0);
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Command::Forward(units) => write!(f, "forward {}", units),
+            Command::Down(units)    => write!(f, "down {}", units),
+            Command::Up(units)      => write!(f, "up {}", units),
+        }
+    }
+}
 
-    let f = File::open("input.txt").expect("Could not open file");
+// How commands are interpreted: part 1 moves the depth directly, part 2 steers through the aim
+#[derive(Copy, Clone, Debug)]
+enum Model {
+    Direct,
+    Aimed,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Submarine {
+    pos: Pos,
+    aim: i64,
+}
+
+impl Submarine {
+    fn new() -> Submarine {
+        Submarine {pos: Pos::new(0, 0), aim: 0}
+    }
+
+    fn execute(&mut self, command: Command, model: Model) {
+        match model {
+            Model::Direct => match command {
+                Command::Forward(units) => self.pos.x += units,
+                Command::Down(units)    => self.pos.y += units,
+                Command::Up(units)      => self.pos.y -= units,
+            },
+            Model::Aimed => match command {
+                Command::Forward(units) => {
+                    self.pos.x += units;
+                    self.pos.y += self.aim * units;
+                },
+                Command::Down(units) => self.aim += units,
+                Command::Up(units) => self.aim -= units,
+            },
+        }
+    }
+}
+
+fn read_commands(fname: &str) -> Vec<Command> {
+    let f = File::open(fname).expect("Could not open file");
     let reader = BufReader::new(f);
+    let mut commands = Vec::new();
     for line in reader.lines() {
         let command = line.expect("Could not read line");
         let command = Command::from_string(&command).expect("Invalid command string");
-        match command {
-            Command::Forward(units) => pos.x += units,
-            Command::Down(units)    => pos.y += units,
-            Command::Up(units)      => pos.y -= units,
-        };
+        commands.push(command);
+    }
+    commands
+}
+
+fn navigate(commands: &[Command], model: Model) -> Trajectory {
+    let mut submarine = Submarine::new();
+    let mut trajectory = Trajectory::new();
+    trajectory.record(submarine.pos.x, submarine.pos.y, submarine.aim);
+    for &command in commands {
+        submarine.execute(command, model);
+        trajectory.record(submarine.pos.x, submarine.pos.y, submarine.aim);
+    }
+    trajectory
+}
+
+// Writes the path for inspection
+fn export(trajectory: &Trajectory, name: &str) {
+    let csv = File::create(format!("{}.csv", name)).expect("Could not create CSV file");
+    trajectory.write_csv(&mut BufWriter::new(csv)).expect("Could not write CSV file");
+    let svg = File::create(format!("{}.svg", name)).expect("Could not create SVG file");
+    trajectory.write_svg(&mut BufWriter::new(svg)).expect("Could not write SVG file");
+    println!("Trajectory written to {}.csv and {}.svg", name, name);
+}
+
+fn report(trajectory: &Trajectory) {
+    let last = trajectory.last().expect("Empty trajectory");
+    println!("Recorded {} commands", trajectory.samples().len() - 1);
+    if let Some((step, depth)) = trajectory.max_depth() {
+        println!("Maximum depth {} reached at step {}", depth, step);
     }
     // Print final result
-    println!("Final position ({}, {}); product = {}", pos.x, pos.y, pos.x * pos.y);
+    println!("Final position ({}, {}); product = {}", last.x, last.depth, last.x * last.depth);
 }
 
-fn part2() {
-    let mut pos = Pos::new(0, 0);
-    let mut aim = 0;
+fn part1(export_trajectory: bool) {
+    let commands = read_commands("input.txt");
+    let trajectory = navigate(&commands, Model::Direct);
+    report(&trajectory);
+    if export_trajectory {
+        export(&trajectory, "trajectory_part1");
+    }
+}
 
-    let f = File::open("input.txt").expect("Could not open file");
-    let reader = BufReader::new(f);
-    for line in reader.lines() {
-        let command = line.expect("Could not read line");
-        let command = Command::from_string(&command).expect("Invalid command string");
-        match command {
-            Command::Forward(units) => {
-                pos.x += units;
-                pos.y += aim * units;
+fn part2(export_trajectory: bool) {
+    let commands = read_commands("input.txt");
+    let trajectory = navigate(&commands, Model::Aimed);
+    report(&trajectory);
+    if export_trajectory {
+        export(&trajectory, "trajectory_part2");
+    }
+}
+
+fn repair(target: &Pos) {
+    let commands = read_commands("input.txt");
+    for model in [Model::Direct, Model::Aimed] {
+        match solver::find_edit(&commands, target, model) {
+            Some(edit) => {
+                let fixed = edit.apply(&commands);
+                let last = *navigate(&fixed, model).last().expect("Empty trajectory");
+                println!("{:?}: {} -> ({}, {})", model, edit, last.x, last.depth);
             },
-            Command::Down(units) => aim += units,
-            Command::Up(units) => aim -= units,
+            None => println!("{:?}: no single edit reaches ({}, {})", model, target.x, target.y),
         }
     }
-    // Print final result
-    println!("Final position ({}, {}); product = {}", pos.x, pos.y, pos.x * pos.y);
 }
@@ -0,0 +1,212 @@
+use std::fmt::{Display, Formatter};
+use crate::{Command, Model, Pos, Submarine};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Edit {
+    // The commands already reach the target
+    Keep,
+    Change { index: usize, command: Command },
+    Delete { index: usize },
+    Insert { index: usize, command: Command },
+}
+
+impl Edit {
+    pub fn apply(&self, commands: &[Command]) -> Vec<Command> {
+        let mut result = Vec::from(commands);
+        match *self {
+            Edit::Keep => (),
+            Edit::Change { index, command } => result[index] = command,
+            Edit::Delete { index } => { result.remove(index); },
+            Edit::Insert { index, command } => result.insert(index, command),
+        }
+        result
+    }
+}
+
+impl Display for Edit {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Edit::Keep => write!(f, "keep all commands"),
+            Edit::Change { index, command } => write!(f, "change command {} to '{}'", index, command),
+            Edit::Delete { index } => write!(f, "delete command {}", index),
+            Edit::Insert { index, command } => write!(f, "insert '{}' before command {}", command, index),
+        }
+    }
+}
+
+// Runs the effect of `suffix` (measured starting from the origin) after reaching `prefix`
+fn compose(prefix: &Submarine, suffix: &Submarine, model: Model) -> Submarine {
+    let mut result = *prefix;
+    result.pos.x += suffix.pos.x;
+    result.pos.y += suffix.pos.y;
+    result.aim += suffix.aim;
+    if let Model::Aimed = model {
+        // Every forward step of the suffix also sinks by the aim accumulated in the prefix
+        result.pos.y += prefix.aim * suffix.pos.x;
+    }
+    result
+}
+
+fn final_pos(prefix: &Submarine, command: Command, suffix: &Submarine, model: Model) -> Pos {
+    let mut submarine = *prefix;
+    submarine.execute(command, model);
+    compose(&submarine, suffix, model).pos
+}
+
+// The final position is affine in the units of a single command, so two probes are enough to solve for them
+fn solve_units(prefix: &Submarine, kind: Command, suffix: &Submarine, target: &Pos, model: Model) -> Option<i64> {
+    let p0 = final_pos(prefix, kind.with_units(0), suffix, model);
+    let p1 = final_pos(prefix, kind.with_units(1), suffix, model);
+    let mut units = None;
+    for (start, slope, goal) in [(p0.x, p1.x - p0.x, target.x), (p0.y, p1.y - p0.y, target.y)] {
+        if slope == 0 {
+            if start != goal {
+                return None;
+            }
+        } else {
+            if (goal - start) % slope != 0 {
+                return None;
+            }
+            let u = (goal - start) / slope;
+            if units.is_some_and(|v| v != u) {
+                return None;
+            }
+            units = Some(u);
+        }
+    }
+    units.filter(|&u| u > 0)
+}
+
+// Finds the cheapest single edit making the run end at `target`. Every edit costs the number of units it adds or
+// removes: changing `down 5` into `down 8` costs 3, deleting `up 4` costs 4. Ties are broken by the first edit found,
+// scanning changes, then deletions, then insertions, each from the first command onwards.
+pub fn find_edit(commands: &[Command], target: &Pos, model: Model) -> Option<Edit> {
+    let n = commands.len();
+    // prefixes[i] is the state before command i, suffixes[i] the effect of commands i.. from the origin
+    let mut prefixes = Vec::with_capacity(n + 1);
+    prefixes.push(Submarine::new());
+    for &command in commands {
+        let mut next = *prefixes.last().unwrap();
+        next.execute(command, model);
+        prefixes.push(next);
+    }
+    let mut suffixes = vec![Submarine::new(); n + 1];
+    for i in (0..n).rev() {
+        let mut head = Submarine::new();
+        head.execute(commands[i], model);
+        suffixes[i] = compose(&head, &suffixes[i + 1], model);
+    }
+    if prefixes[n].pos == *target {
+        return Some(Edit::Keep);
+    }
+
+    let mut best: Option<(i64, Edit)> = None;
+    let mut consider = |cost: i64, edit: Edit| {
+        if best.is_none_or(|(best_cost, _)| cost < best_cost) {
+            best = Some((cost, edit));
+        }
+    };
+    for (index, &command) in commands.iter().enumerate() {
+        if let Some(units) = solve_units(&prefixes[index], command, &suffixes[index + 1], target, model) {
+            consider((units - command.units()).abs(), Edit::Change { index, command: command.with_units(units) });
+        }
+    }
+    for (index, &command) in commands.iter().enumerate() {
+        if compose(&prefixes[index], &suffixes[index + 1], model).pos == *target {
+            consider(command.units().abs(), Edit::Delete { index });
+        }
+    }
+    for index in 0..=n {
+        for kind in [Command::Forward(0), Command::Down(0), Command::Up(0)] {
+            if let Some(units) = solve_units(&prefixes[index], kind, &suffixes[index], target, model) {
+                consider(units, Edit::Insert { index, command: kind.with_units(units) });
+            }
+        }
+    }
+    best.map(|(_, edit)| edit)
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Vec<Command> {
+        ["forward 5", "down 5", "forward 8", "up 3", "down 8", "forward 2"]
+            .map(|s| Command::from_string(s).unwrap())
+            .to_vec()
+    }
+
+    fn run(commands: &[Command], model: Model) -> Pos {
+        let mut submarine = Submarine::new();
+        for &command in commands {
+            submarine.execute(command, model);
+        }
+        submarine.pos
+    }
+
+    fn cost(commands: &[Command], edit: &Edit) -> i64 {
+        match *edit {
+            Edit::Keep => 0,
+            Edit::Change { index, command } => (command.units() - commands[index].units()).abs(),
+            Edit::Delete { index } => commands[index].units(),
+            Edit::Insert { command, .. } => command.units(),
+        }
+    }
+
+    // Tries every single edit with units up to `max_units`
+    fn brute_force(commands: &[Command], target: &Pos, model: Model, max_units: i64) -> Option<i64> {
+        let mut candidates = Vec::new();
+        for index in 0..=commands.len() {
+            if index < commands.len() {
+                candidates.push(Edit::Delete { index });
+            }
+            for units in 1..=max_units {
+                if index < commands.len() {
+                    candidates.push(Edit::Change { index, command: commands[index].with_units(units) });
+                }
+                for kind in [Command::Forward(units), Command::Down(units), Command::Up(units)] {
+                    candidates.push(Edit::Insert { index, command: kind });
+                }
+            }
+        }
+        candidates.iter()
+            .filter(|edit| run(&edit.apply(commands), model) == *target)
+            .map(|edit| cost(commands, edit))
+            .min()
+    }
+
+    #[test]
+    fn already_on_target() {
+        let commands = example();
+        assert_eq!(find_edit(&commands, &Pos::new(15, 10), Model::Direct), Some(Edit::Keep));
+        assert_eq!(find_edit(&commands, &Pos::new(15, 60), Model::Aimed), Some(Edit::Keep));
+    }
+
+    #[test]
+    fn unreachable_target() {
+        // A single edit cannot undo all the forward movement
+        assert_eq!(find_edit(&example(), &Pos::new(0, 0), Model::Direct), None);
+        assert_eq!(find_edit(&example(), &Pos::new(0, 0), Model::Aimed), None);
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let commands = example();
+        for model in [Model::Direct, Model::Aimed] {
+            for x in 10..=25 {
+                for y in (0..=80).step_by(4) {
+                    let target = Pos::new(x, y);
+                    let edit = find_edit(&commands, &target, model);
+                    if let Some(edit) = edit {
+                        assert_eq!(run(&edit.apply(&commands), model), target, "{} misses {:?}", edit, target);
+                    }
+                    let expected = if run(&commands, model) == target { Some(0) } else {
+                        brute_force(&commands, &target, model, 100)
+                    };
+                    assert_eq!(edit.map(|e| cost(&commands, &e)), expected, "Wrong cost for {:?} ({:?})", target, model);
+                }
+            }
+        }
+    }
+}
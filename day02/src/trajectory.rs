@@ -0,0 +1,140 @@
+use std::io::{Result, Write};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub x: i64,
+    pub depth: i64,
+    pub aim: i64,
+}
+
+impl Sample {
+    pub fn new(x: i64, depth: i64, aim: i64) -> Sample {
+        Sample {x, depth, aim}
+    }
+}
+
+// Step 0 is the starting state, step n is the state right after the n-th command
+pub struct Trajectory {
+    samples: Vec<Sample>,
+}
+
+impl Trajectory {
+    const SVG_WIDTH: f64 = 800.0;
+    const SVG_HEIGHT: f64 = 400.0;
+    const SVG_MARGIN: f64 = 10.0;
+
+    pub fn new() -> Trajectory {
+        Trajectory {
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, x: i64, depth: i64, aim: i64) {
+        self.samples.push(Sample::new(x, depth, aim));
+    }
+
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    pub fn last(&self) -> Option<&Sample> {
+        self.samples.last()
+    }
+
+    // Returns the first step at which the maximum depth is reached, together with the depth itself
+    pub fn max_depth(&self) -> Option<(usize, i64)> {
+        let mut result: Option<(usize, i64)> = None;
+        for (step, sample) in self.samples.iter().enumerate() {
+            match result {
+                Some((_, depth)) if depth >= sample.depth => (),
+                _ => result = Some((step, sample.depth)),
+            }
+        }
+        result
+    }
+
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writeln!(writer, "step,x,depth,aim")?;
+        for (step, sample) in self.samples.iter().enumerate() {
+            writeln!(writer, "{},{},{},{}", step, sample.x, sample.depth, sample.aim)?;
+        }
+        Ok(())
+    }
+
+    // Draws the path as seen from the side: x grows to the right, depth grows downwards
+    pub fn write_svg<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let (width, height, margin) = (Self::SVG_WIDTH, Self::SVG_HEIGHT, Self::SVG_MARGIN);
+        writeln!(writer, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+                 width + 2.0 * margin, height + 2.0 * margin, width + 2.0 * margin, height + 2.0 * margin)?;
+        if !self.samples.is_empty() {
+            let xmin = self.samples.iter().map(|s| s.x).min().unwrap();
+            let xmax = self.samples.iter().map(|s| s.x).max().unwrap();
+            let dmin = self.samples.iter().map(|s| s.depth).min().unwrap();
+            let dmax = self.samples.iter().map(|s| s.depth).max().unwrap();
+            // Avoid dividing by zero on degenerate paths
+            let xspan = ((xmax - xmin) as f64).max(1.0);
+            let dspan = ((dmax - dmin) as f64).max(1.0);
+            write!(writer, r#"  <polyline fill="none" stroke="navy" stroke-width="1" points=""#)?;
+            for (i, sample) in self.samples.iter().enumerate() {
+                let px = margin + (sample.x - xmin) as f64 / xspan * width;
+                let py = margin + (sample.depth - dmin) as f64 / dspan * height;
+                if i > 0 {
+                    write!(writer, " ")?;
+                }
+                write!(writer, "{:.2},{:.2}", px, py)?;
+            }
+            writeln!(writer, r#""/>"#)?;
+        }
+        writeln!(writer, "</svg>")
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_trajectory() -> Trajectory {
+        let mut trajectory = Trajectory::new();
+        trajectory.record(0, 0, 0);
+        trajectory.record(5, 0, 0);
+        trajectory.record(5, 0, 5);
+        trajectory.record(13, 40, 5);
+        trajectory.record(13, 40, 2);
+        trajectory.record(15, 44, 2);
+        trajectory.record(15, 44, 0);
+        trajectory.record(17, 44, 0);
+        trajectory
+    }
+
+    #[test]
+    fn max_depth_first_step() {
+        let trajectory = sample_trajectory();
+        assert_eq!(trajectory.max_depth(), Some((5, 44)));
+        assert_eq!(Trajectory::new().max_depth(), None);
+    }
+
+    #[test]
+    fn csv_export() {
+        let mut trajectory = Trajectory::new();
+        trajectory.record(0, 0, 0);
+        trajectory.record(5, 10, 2);
+        let mut output = Vec::new();
+        trajectory.write_csv(&mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "step,x,depth,aim\n0,0,0,0\n1,5,10,2\n");
+    }
+
+    #[test]
+    fn svg_export() {
+        let trajectory = sample_trajectory();
+        let mut output = Vec::new();
+        trajectory.write_svg(&mut output).unwrap();
+        let svg = String::from_utf8(output).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        // One vertex per sample, the first one in the top-left corner
+        let points = svg.split("points=\"").nth(1).unwrap().split('"').next().unwrap();
+        assert_eq!(points.split(' ').count(), trajectory.samples().len());
+        assert!(points.starts_with("10.00,10.00"));
+    }
+}
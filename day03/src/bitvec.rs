@@ -0,0 +1,157 @@
+use std::fmt;
+use std::num::ParseIntError;
+
+// Fixed-length sequence of bits. Bit 0 is the least significant one, bits past `len` are always zero so that
+// equality and hashing only depend on the visible bits.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    const WORD_BITS: usize = u64::BITS as usize;
+
+    pub fn new(len: usize) -> BitVec {
+        BitVec {
+            words: vec![0; len.div_ceil(Self::WORD_BITS)],
+            len,
+        }
+    }
+
+    pub fn from_binary_str(string: &str) -> Result<BitVec, ParseIntError> {
+        // Let the standard parser produce the error for empty strings, signs and invalid digits
+        if string.is_empty() {
+            u64::from_str_radix(string, 2)?;
+        }
+        for c in string.chars().filter(|&c| c != '0' && c != '1') {
+            u64::from_str_radix(&c.to_string(), 2)?;
+        }
+        let mut bits = BitVec::new(string.len());
+        // Parse one word at a time, starting from the least significant digits
+        let mut end = string.len();
+        for word in bits.words.iter_mut() {
+            let start = end.saturating_sub(Self::WORD_BITS);
+            *word = u64::from_str_radix(&string[start..end], 2)?;
+            end = start;
+        }
+        Ok(bits)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn get(&self, pos: usize) -> bool {
+        assert!(pos < self.len, "Invalid bit position");
+        (self.words[pos / Self::WORD_BITS] >> (pos % Self::WORD_BITS)) & 0x1 == 1
+    }
+
+    pub fn set(&mut self, pos: usize, val: bool) {
+        assert!(pos < self.len, "Invalid bit position");
+        let mask = 0x1u64 << (pos % Self::WORD_BITS);
+        if val {
+            self.words[pos / Self::WORD_BITS] |= mask;
+        } else {
+            self.words[pos / Self::WORD_BITS] &= !mask;
+        }
+    }
+
+    // Unsigned product, wide enough to never overflow
+    pub fn mul(&self, other: &BitVec) -> BitVec {
+        let mut result = BitVec::new(self.len + other.len);
+        for (i, &a) in self.words.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, &b) in other.words.iter().enumerate() {
+                let acc = result.words[i + j] as u128 + (a as u128) * (b as u128) + carry;
+                result.words[i + j] = acc as u64;
+                carry = acc >> Self::WORD_BITS;
+            }
+            let mut k = i + other.words.len();
+            while carry > 0 {
+                let acc = result.words[k] as u128 + carry;
+                result.words[k] = acc as u64;
+                carry = acc >> Self::WORD_BITS;
+                k += 1;
+            }
+        }
+        result
+    }
+
+    fn to_decimal_string(&self) -> String {
+        // Repeatedly divide by the largest power of ten fitting a word
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut words = self.words.clone();
+        let mut chunks = Vec::new();
+        while words.iter().any(|&word| word != 0) {
+            let mut remainder = 0u128;
+            for word in words.iter_mut().rev() {
+                let acc = (remainder << Self::WORD_BITS) | *word as u128;
+                *word = (acc / CHUNK as u128) as u64;
+                remainder = acc % CHUNK as u128;
+            }
+            chunks.push(remainder as u64);
+        }
+        match chunks.pop() {
+            None => String::from("0"),
+            Some(first) => {
+                let mut string = first.to_string();
+                for chunk in chunks.iter().rev() {
+                    string.push_str(&format!("{:019}", chunk));
+                }
+                string
+            }
+        }
+    }
+}
+
+impl fmt::Binary for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits: String = (0..self.len).rev().map(|pos| if self.get(pos) { '1' } else { '0' }).collect();
+        f.pad_integral(true, "0b", if digits.is_empty() { "0" } else { &digits })
+    }
+}
+
+impl fmt::Display for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "", &self.to_decimal_string())
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_wide_string() {
+        let string = format!("1{}1", "0".repeat(70));
+        let bits = BitVec::from_binary_str(&string).unwrap();
+        assert_eq!(bits.len(), 72);
+        assert!(bits.get(0) && bits.get(71));
+        assert!((1..71).all(|pos| !bits.get(pos)));
+        assert_eq!(format!("{:b}", bits), string);
+        assert!(BitVec::from_binary_str("10201").is_err());
+        assert!(BitVec::from_binary_str("").is_err());
+        assert!(BitVec::from_binary_str("+101").is_err());
+    }
+
+    #[test]
+    fn binary_keeps_width() {
+        let bits = BitVec::from_binary_str("00101").unwrap();
+        assert_eq!(format!("{:b}", bits), "00101");
+        assert_eq!(bits.to_string(), "5");
+    }
+
+    #[test]
+    fn wide_product_and_decimal() {
+        // (2^64 + 1) * (2^64 - 1) = 2^128 - 1
+        let a = BitVec::from_binary_str(&format!("1{}1", "0".repeat(63))).unwrap();
+        let b = BitVec::from_binary_str(&"1".repeat(64)).unwrap();
+        assert_eq!(a.mul(&b).to_string(), u128::MAX.to_string());
+        assert_eq!(BitVec::new(3).to_string(), "0");
+        let gamma = BitVec::from_binary_str("000010110").unwrap();
+        let epsilon = BitVec::from_binary_str("01001").unwrap();
+        assert_eq!(gamma.mul(&epsilon).to_string(), "198");
+    }
+}
@@ -0,0 +1,162 @@
+use std::cmp::Ordering;
+use crate::trie::BitTrie;
+use crate::{BinaryNumber, BitCounter};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ScanDirection {
+    MsbFirst,
+    LsbFirst,
+}
+
+impl ScanDirection {
+    pub fn positions(&self, width: usize) -> Vec<usize> {
+        match self {
+            ScanDirection::MsbFirst => (0..width).rev().collect(),
+            ScanDirection::LsbFirst => (0..width).collect(),
+        }
+    }
+}
+
+type Predicate = Box<dyn Fn(usize, u64, u64) -> Option<u8>>;
+
+// Rule picking a bit value for each position, given how many entries have a 0 and a 1 there. The predicate returns
+// `None` on a tie, which is then resolved by the tie-break bit.
+pub struct BitCriterion {
+    predicate: Predicate,
+    tie_break: u8,
+    direction: ScanDirection,
+}
+
+impl BitCriterion {
+    pub fn custom<F>(predicate: F) -> BitCriterion
+    where
+        F: Fn(usize, u64, u64) -> Option<u8> + 'static,
+    {
+        BitCriterion {
+            predicate: Box::new(predicate),
+            tie_break: 1,
+            direction: ScanDirection::MsbFirst,
+        }
+    }
+
+    pub fn most_common() -> BitCriterion {
+        BitCriterion::custom(|_, zeros, ones| match ones.cmp(&zeros) {
+            Ordering::Greater => Some(1),
+            Ordering::Equal   => None,
+            Ordering::Less    => Some(0),
+        })
+    }
+
+    pub fn least_common() -> BitCriterion {
+        BitCriterion::custom(|_, zeros, ones| match ones.cmp(&zeros) {
+            Ordering::Greater => Some(0),
+            Ordering::Equal   => None,
+            Ordering::Less    => Some(1),
+        })
+    }
+
+    pub fn with_tie_break(mut self, bit: u8) -> BitCriterion {
+        assert!(bit <= 1, "Invalid bit value");
+        self.tie_break = bit;
+        self
+    }
+
+    pub fn with_direction(mut self, direction: ScanDirection) -> BitCriterion {
+        self.direction = direction;
+        self
+    }
+
+    pub fn choose(&self, pos: usize, zeros: u64, ones: u64) -> u8 {
+        let bit = (self.predicate)(pos, zeros, ones).unwrap_or(self.tie_break);
+        assert!(bit <= 1, "Criterion chose invalid bit value {} at position {}", bit, pos);
+        bit
+    }
+
+    // Applies the criterion to every position independently, as for the gamma and epsilon rates
+    pub fn rate(&self, counter: &BitCounter) -> BinaryNumber {
+        let mut number = BinaryNumber::new(counter.size());
+        for pos in self.direction.positions(counter.size()) {
+            number.set_bit(pos, self.choose(pos, counter.zeros(pos), counter.ones(pos)));
+        }
+        number
+    }
+
+    // Keeps only the entries matching the criterion, one position at a time, until a single one is left
+    pub fn select(&self, trie: &BitTrie) -> Option<BinaryNumber> {
+        assert_eq!(trie.direction(), self.direction, "Trie and criterion scan in different directions");
+        trie.walk(|pos, zeros, ones| self.choose(pos, zeros, ones))
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [&str; 12] = ["00100", "11110", "10110", "10111", "10101", "01111",
+                                 "00111", "11100", "10000", "11001", "00010", "01010"];
+
+    fn numbers() -> Vec<BinaryNumber> {
+        EXAMPLE.iter().map(|s| BinaryNumber::from_binary_str(s).unwrap()).collect()
+    }
+
+    fn trie(direction: ScanDirection) -> BitTrie {
+        let mut trie = BitTrie::new(5, direction);
+        for number in numbers() {
+            trie.insert(&number, 1);
+        }
+        trie
+    }
+
+    #[test]
+    fn power_consumption() {
+        let mut counter = BitCounter::new(5);
+        for number in numbers() {
+            counter.count(&number);
+        }
+        assert_eq!(BitCriterion::most_common().rate(&counter).to_string(), "22");
+        assert_eq!(BitCriterion::least_common().with_tie_break(0).rate(&counter).to_string(), "9");
+    }
+
+    #[test]
+    fn life_support() {
+        let trie = trie(ScanDirection::MsbFirst);
+        let oxygen = BitCriterion::most_common().with_tie_break(1).select(&trie).unwrap();
+        let co2 = BitCriterion::least_common().with_tie_break(0).select(&trie).unwrap();
+        assert_eq!(oxygen.to_string(), "23");
+        assert_eq!(co2.to_string(), "10");
+    }
+
+    #[test]
+    fn tie_break() {
+        // Two entries differing in every bit always tie
+        let mut trie = BitTrie::new(3, ScanDirection::MsbFirst);
+        trie.insert(&BinaryNumber::from_binary_str("101").unwrap(), 1);
+        trie.insert(&BinaryNumber::from_binary_str("010").unwrap(), 1);
+        assert_eq!(BitCriterion::most_common().with_tie_break(1).select(&trie).unwrap().to_string(), "5");
+        assert_eq!(BitCriterion::most_common().with_tie_break(0).select(&trie).unwrap().to_string(), "2");
+    }
+
+    #[test]
+    fn lsb_first() {
+        // Filtering from the LSB leads to a different entry than the usual oxygen rating
+        let criterion = BitCriterion::most_common().with_direction(ScanDirection::LsbFirst);
+        let oxygen = criterion.select(&trie(ScanDirection::LsbFirst)).unwrap();
+        assert_eq!(format!("{:b}", oxygen), "11110");
+    }
+
+    #[test]
+    fn custom_predicate() {
+        // Prefer ones on even positions and zeros on odd positions, whatever the counts
+        let criterion = BitCriterion::custom(|pos, _, _| Some(if pos % 2 == 0 { 1 } else { 0 }));
+        let number = criterion.select(&trie(ScanDirection::MsbFirst)).unwrap();
+        assert_eq!(format!("{:b}", number), "10101");
+    }
+
+    #[test]
+    #[should_panic(expected = "Criterion chose invalid bit value 2 at position 4")]
+    fn invalid_bit() {
+        let criterion = BitCriterion::custom(|_, _, _| Some(2));
+        criterion.select(&trie(ScanDirection::MsbFirst));
+    }
+}
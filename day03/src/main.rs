@@ -1,114 +1,125 @@
-use std::error::Error;
-use std::fs::{File, read};
-use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom};
 use std::num::ParseIntError;
 use std::fmt;
-use std::fmt::{Binary, format};
-use std::cmp::Ordering;
-use std::collections::HashMap;
+use crate::bitvec::BitVec;
+use crate::criterion::{BitCriterion, ScanDirection};
+use crate::report::DiagnosticReport;
+use crate::trie::BitTrie;
 
+mod bitvec;
+mod criterion;
+mod report;
+mod trie;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
 struct BinaryNumber {
-    num: u64,
-    len: u8,
+    bits: BitVec,
 }
 
 impl BinaryNumber {
-    fn new(len: u8) -> BinaryNumber {
-        assert!(len < 64, "Maximum size is 64");
-        BinaryNumber{num: 0, len}
-    }
-
-    fn from(num: u64, len: u8) -> BinaryNumber {
-        BinaryNumber{num, len}
+    fn new(len: usize) -> BinaryNumber {
+        BinaryNumber{bits: BitVec::new(len)}
     }
 
     fn from_binary_str(string: &str) -> Result<BinaryNumber, ParseIntError> {
-        assert!(string.len() < 64, "Maximum size is 64");
-        let num = u64::from_str_radix(string, 2)?;
-        Ok(BinaryNumber{num, len: string.len() as u8})
+        Ok(BinaryNumber{bits: BitVec::from_binary_str(string)?})
     }
 
-    fn len(&self) -> u8 {
-        self.len
+    fn len(&self) -> usize {
+        self.bits.len()
     }
 
-    fn bit(&self, pos: u8) -> u8 {
-        assert!(pos < self.len, "Invalid bit position");
-        ((self.num >> pos) & 0x1) as u8
+    fn bit(&self, pos: usize) -> u8 {
+        self.bits.get(pos) as u8
     }
 
-    fn set_bit(&mut self, pos: u8, val: u8) {
-        assert!(pos < self.len,       "Invalid bit position");
-        if val == 0 {
-            self.num &= !(0x1 << pos) as u64;
-        } else if val == 1 {
-            self.num |= (0x1 << pos) as u64;
-        } else {
-            panic!("Invalid bit value");
+    fn set_bit(&mut self, pos: usize, val: u8) {
+        match val {
+            0 => self.bits.set(pos, false),
+            1 => self.bits.set(pos, true),
+            _ => panic!("Invalid bit value"),
         }
     }
+
+    fn product(&self, other: &BinaryNumber) -> BitVec {
+        self.bits.mul(&other.bits)
+    }
 }
 
 impl fmt::Binary for BinaryNumber {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:0>16b}", self.num)
+        fmt::Binary::fmt(&self.bits, f)
     }
 }
 impl fmt::Display for BinaryNumber {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.num)
+        fmt::Display::fmt(&self.bits, f)
     }
 }
 
 struct BitCounter {
     counts: Vec<u64>,
     entries: u64,
+    // Optional size x size matrix counting entries having both bits set
+    pairs: Option<Vec<u64>>,
 }
 
 impl BitCounter {
     fn new(size: usize) -> BitCounter {
-        assert!(size < 64, "Maximum size is 64");
         BitCounter {
             counts: vec![0; size],
-            entries: 0
+            entries: 0,
+            pairs: None,
         }
     }
 
-    fn from(values: &HashMap<u64, u64>, size: usize) -> BitCounter {
+    fn with_pairs(size: usize) -> BitCounter {
         let mut counter = BitCounter::new(size);
-        for (value, count) in values {
-            let binarynum = BinaryNumber::from(*value, size as u8);
-            counter.count_multiple(binarynum, *count);
-        }
+        counter.pairs = Some(vec![0; size * size]);
         counter
     }
 
-    fn count(&mut self, num: BinaryNumber) {
+    fn count(&mut self, num: &BinaryNumber) {
         self.count_multiple(num, 1);
     }
 
-    fn count_multiple(&mut self, num: BinaryNumber, cnt: u64) {
-        assert_eq!(num.len() as usize, self.counts.len(), "Size mismatch");
-        for i in 0..num.len as u8{
-            self.counts[i as usize] += (num.bit(i) as u64) * cnt;
+    fn count_multiple(&mut self, num: &BinaryNumber, cnt: u64) {
+        assert_eq!(num.len(), self.counts.len(), "Size mismatch");
+        for i in 0..num.len() {
+            self.counts[i] += (num.bit(i) as u64) * cnt;
+        }
+        if let Some(pairs) = &mut self.pairs {
+            let size = self.counts.len();
+            let set: Vec<usize> = (0..size).filter(|&i| num.bit(i) == 1).collect();
+            for &i in &set {
+                for &j in &set {
+                    pairs[i * size + j] += cnt;
+                }
+            }
         }
         self.entries += cnt;
     }
 
-    fn most_common_bit(&self, pos: u8) -> Option<u8> {
-        match self.counts[pos as usize].cmp(&((self.entries + 1) / 2)) {
-            Ordering::Greater => Some(1),
-            Ordering::Equal   => None,
-            Ordering::Less    => Some(0),
-        }
+    fn size(&self) -> usize {
+        self.counts.len()
     }
 
-    fn least_common_bit(&self, pos: u8) -> Option<u8> {
-        match self.counts[pos as usize].cmp(&((self.entries + 1) / 2)) {
-            Ordering::Greater => Some(0),
-            Ordering::Equal   => None,
-            Ordering::Less    => Some(1),
-        }
+    fn ones(&self, pos: usize) -> u64 {
+        self.counts[pos]
+    }
+
+    fn zeros(&self, pos: usize) -> u64 {
+        self.entries - self.counts[pos]
+    }
+
+    fn entries(&self) -> u64 {
+        self.entries
+    }
+
+    // Number of entries having both bits set, if pairs are being counted
+    fn both(&self, a: usize, b: usize) -> Option<u64> {
+        self.pairs.as_ref().map(|pairs| pairs[a * self.counts.len() + b])
     }
 }
 
@@ -132,76 +143,69 @@ fn part1() {
     for line in reader.lines() {
         let line = line.expect("Could not read line");
         let bnum = BinaryNumber::from_binary_str(&line).expect("Could not parse number");
-        counter.count(bnum);
-    }
-    // Generate gamma
-    let size = size as u8;
-    let mut gamma = BinaryNumber::new(size);
-    for i in 0..size {
-        gamma.set_bit(i, counter.most_common_bit(i).unwrap_or(1));
-    }
-    // Generate epsilon
-    let mut epsilon = BinaryNumber::new(size);
-    for i in 0..size {
-        epsilon.set_bit(i, counter.least_common_bit(i).unwrap_or(0));
+        counter.count(&bnum);
     }
-    println!("G = {}\tE = {}\tG*E = {}", gamma, epsilon, gamma.num * epsilon.num);
+    // Generate gamma and epsilon
+    let gamma = BitCriterion::most_common().with_tie_break(1).rate(&counter);
+    let epsilon = BitCriterion::least_common().with_tie_break(0).rate(&counter);
+    println!("G = {}\tE = {}\tG*E = {}", gamma, epsilon, gamma.product(&epsilon));
 }
 
-fn filter(mut data: HashMap<u64, u64>, pos: u8, val: u8) -> HashMap<u64, u64> {
-    let mut numbers_to_remove = Vec::new();
-    for number in data.keys() {
-        let number = BinaryNumber::from(*number, 64);
-        if number.bit(pos) != val {
-            numbers_to_remove.push(number.num);
-        }
-    }
-    for number_to_remove in &numbers_to_remove {
-        data.remove(number_to_remove);
-    }
-    data
-}
-
-fn part2() {
+fn part2(direction: ScanDirection) {
     println!("{:-<80}", "Part 2 ");
     let f = File::open("data.txt").expect("Could not open file");
     let (reader, size) = get_reader_and_entry_size(&f);
-    let mut data = HashMap::new();
+    let mut trie = BitTrie::new(size, direction);
     for line in reader.lines() {
         let line = line.expect("Could not read line");
         let bnum = BinaryNumber::from_binary_str(&line).expect("Could not parse number");
-        data.insert(bnum.num, data.get(&bnum.num).unwrap_or(&0) + 1);
-    }
-    // Make data immutable
-    let data = data;
-    // Filter data, starting from the MSB, to find the oxygen rating
-    let size = size as u8;
-    let mut oxygen_data = data.clone();
-    let mut i = size - 1;
-    while oxygen_data.len() > 1 {
-        let counter = BitCounter::from(&oxygen_data, size as usize);
-        let bit = counter.most_common_bit(i).unwrap_or(1);
-        oxygen_data = filter(oxygen_data, i, bit);
-        i = i.wrapping_sub(1);
-    }
-    let oxygen_rating = *oxygen_data.keys().collect::<Vec<&u64>>()[0];
+        trie.insert(&bnum, 1);
+    }
+    // Follow the most common bits to find the oxygen rating
+    let oxygen = BitCriterion::most_common().with_tie_break(1).with_direction(direction);
+    let oxygen_rating = oxygen.select(&trie).expect("No oxygen rating");
     println!("O2: {}", oxygen_rating);
-    // Filter data, starting from the MSB, to find the co2 rating
-    let size = size as u8;
-    let mut co2_data = data.clone();
-    let mut i = size - 1;
-    while co2_data.len() > 1 {
-        let counter = BitCounter::from(&co2_data, size as usize);
-        let bit = counter.least_common_bit(i).unwrap_or(0);
-        co2_data = filter(co2_data, i, bit);
-        i = i.wrapping_sub(1);
-    }
-    let co2_rating = *co2_data.keys().collect::<Vec<&u64>>()[0];
+    // Follow the least common bits to find the co2 rating
+    let co2 = BitCriterion::least_common().with_tie_break(0).with_direction(direction);
+    let co2_rating = co2.select(&trie).expect("No CO2 rating");
     println!("CO2: {}", co2_rating);
-    println!("Product: {}", co2_rating * oxygen_rating);
+    println!("Product: {}", co2_rating.product(&oxygen_rating));
+}
+
+fn report(json: Option<&str>) {
+    println!("{:-<80}", "Report ");
+    let f = File::open("data.txt").expect("Could not open file");
+    let (reader, size) = get_reader_and_entry_size(&f);
+    let mut counter = BitCounter::with_pairs(size);
+    for line in reader.lines() {
+        let line = line.expect("Could not read line");
+        let bnum = BinaryNumber::from_binary_str(&line).expect("Could not parse number");
+        counter.count(&bnum);
+    }
+    let report = DiagnosticReport::new(&counter);
+    print!("{}", report);
+    if let Some((a, b, correlation)) = report.strongest_correlation() {
+        println!("Strongest correlation: bits {} and {} ({:.3})", a, b, correlation);
+    }
+    if let Some(path) = json {
+        let f = File::create(path).expect("Could not create report file");
+        report.write_json(&mut BufWriter::new(f)).expect("Could not write report");
+        println!("Report written to {}", path);
+    }
 }
 
 fn main() {
+    // The puzzle filters from the MSB, but the other way round can be requested with --lsb-first. The report is
+    // only written as JSON when a path is given with --json.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let direction = if args.iter().any(|arg| arg == "--lsb-first") {
+        ScanDirection::LsbFirst
+    } else {
+        ScanDirection::MsbFirst
+    };
+    let json = args.iter().position(|arg| arg == "--json")
+        .map(|i| args.get(i + 1).expect("Missing path after --json").as_str());
     part1();
-    part2();
+    part2(direction);
+    report(json);
 }
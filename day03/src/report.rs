@@ -0,0 +1,171 @@
+use std::fmt::{Display, Formatter};
+use std::io::{Result, Write};
+use crate::BitCounter;
+
+#[derive(Debug, PartialEq)]
+pub struct BitStats {
+    pub pos: usize,
+    pub ones: u64,
+    pub zeros: u64,
+    // (ones - zeros) / entries: 1 when every entry has the bit set, -1 when none has
+    pub balance: f64,
+    // Shannon entropy of the bit, in bits
+    pub entropy: f64,
+}
+
+impl BitStats {
+    fn new(pos: usize, ones: u64, zeros: u64) -> BitStats {
+        let entries = (ones + zeros) as f64;
+        let (balance, entropy) = if entries > 0.0 {
+            let balance = (ones as f64 - zeros as f64) / entries;
+            let entropy = [ones, zeros].iter()
+                .map(|&count| count as f64 / entries)
+                .filter(|&p| p > 0.0)
+                .map(|p| -p * p.log2())
+                .sum();
+            (balance, entropy)
+        } else {
+            (0.0, 0.0)
+        };
+        BitStats {pos, ones, zeros, balance, entropy}
+    }
+}
+
+pub struct DiagnosticReport {
+    entries: u64,
+    bits: Vec<BitStats>,
+    // Pearson correlation between each pair of bits, undefined when either bit is constant
+    correlations: Vec<Vec<Option<f64>>>,
+}
+
+impl DiagnosticReport {
+    pub fn new(counter: &BitCounter) -> DiagnosticReport {
+        let size = counter.size();
+        let entries = counter.entries();
+        let bits = (0..size).map(|pos| BitStats::new(pos, counter.ones(pos), counter.zeros(pos))).collect();
+        let correlation = |a: usize, b: usize| {
+            let both = counter.both(a, b).expect("Counter does not track bit pairs") as f64;
+            let (ones_a, ones_b) = (counter.ones(a) as f64, counter.ones(b) as f64);
+            let (zeros_a, zeros_b) = (counter.zeros(a) as f64, counter.zeros(b) as f64);
+            let denominator = (ones_a * zeros_a * ones_b * zeros_b).sqrt();
+            if denominator > 0.0 {
+                Some((entries as f64 * both - ones_a * ones_b) / denominator)
+            } else {
+                None
+            }
+        };
+        let correlations = (0..size).map(|a| (0..size).map(|b| correlation(a, b)).collect()).collect();
+        DiagnosticReport {entries, bits, correlations}
+    }
+
+    pub fn correlation(&self, a: usize, b: usize) -> Option<f64> {
+        self.correlations[a][b]
+    }
+
+    // Pair of distinct bits with the largest absolute correlation
+    pub fn strongest_correlation(&self) -> Option<(usize, usize, f64)> {
+        let mut strongest: Option<(usize, usize, f64)> = None;
+        for a in 0..self.bits.len() {
+            for b in a + 1..self.bits.len() {
+                if let Some(correlation) = self.correlation(a, b) {
+                    if strongest.is_none_or(|(_, _, c)| correlation.abs() > c.abs()) {
+                        strongest = Some((a, b, correlation));
+                    }
+                }
+            }
+        }
+        strongest
+    }
+
+    pub fn write_json<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writeln!(writer, "{{")?;
+        writeln!(writer, "  \"entries\": {},", self.entries)?;
+        writeln!(writer, "  \"bits\": [")?;
+        for (i, stats) in self.bits.iter().enumerate() {
+            let separator = if i + 1 < self.bits.len() { "," } else { "" };
+            writeln!(writer, "    {{\"pos\": {}, \"ones\": {}, \"zeros\": {}, \"balance\": {}, \"entropy\": {}}}{}",
+                     stats.pos, stats.ones, stats.zeros, stats.balance, stats.entropy, separator)?;
+        }
+        writeln!(writer, "  ],")?;
+        writeln!(writer, "  \"correlations\": [")?;
+        for (i, row) in self.correlations.iter().enumerate() {
+            let values: Vec<String> = row.iter()
+                .map(|c| c.map_or(String::from("null"), |c| c.to_string()))
+                .collect();
+            let separator = if i + 1 < self.correlations.len() { "," } else { "" };
+            writeln!(writer, "    [{}]{}", values.join(", "), separator)?;
+        }
+        writeln!(writer, "  ]")?;
+        writeln!(writer, "}}")
+    }
+}
+
+impl Display for DiagnosticReport {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "{:>5} {:>10} {:>10} {:>9} {:>9}", "bit", "ones", "zeros", "balance", "entropy")?;
+        // MSB first, as the numbers are written
+        for stats in self.bits.iter().rev() {
+            writeln!(f, "{:>5} {:>10} {:>10} {:>9.4} {:>9.4}",
+                     stats.pos, stats.ones, stats.zeros, stats.balance, stats.entropy)?;
+        }
+        Ok(())
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BinaryNumber;
+
+    fn example() -> DiagnosticReport {
+        let mut counter = BitCounter::with_pairs(5);
+        for line in ["00100", "11110", "10110", "10111", "10101", "01111",
+                     "00111", "11100", "10000", "11001", "00010", "01010"] {
+            counter.count(&BinaryNumber::from_binary_str(line).unwrap());
+        }
+        DiagnosticReport::new(&counter)
+    }
+
+    #[test]
+    fn bit_stats() {
+        let report = example();
+        let msb = &report.bits[4];
+        assert_eq!((msb.ones, msb.zeros), (7, 5));
+        assert!((msb.balance - 2.0 / 12.0).abs() < 1e-12);
+        let expected_entropy = -(7.0 / 12.0f64) * (7.0 / 12.0f64).log2() - (5.0 / 12.0f64) * (5.0 / 12.0f64).log2();
+        assert!((msb.entropy - expected_entropy).abs() < 1e-12);
+        assert_eq!((report.bits[2].ones, report.bits[2].zeros), (8, 4));
+        assert!((report.bits[2].balance - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn correlations() {
+        let report = example();
+        for a in 0..5 {
+            assert!((report.correlation(a, a).unwrap() - 1.0).abs() < 1e-12);
+            for b in 0..5 {
+                assert_eq!(report.correlation(a, b), report.correlation(b, a));
+            }
+        }
+        // Bits 0 and 1 are set together only in 10111, 01111, 00111
+        let expected = (12.0 * 3.0 - 5.0 * 7.0) / (5.0f64 * 7.0 * 7.0 * 5.0).sqrt();
+        assert!((report.correlation(0, 1).unwrap() - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn constant_bit() {
+        let mut counter = BitCounter::with_pairs(2);
+        for line in ["10", "11"] {
+            counter.count(&BinaryNumber::from_binary_str(line).unwrap());
+        }
+        let report = DiagnosticReport::new(&counter);
+        assert_eq!(report.correlation(0, 1), None);
+        assert_eq!(report.strongest_correlation(), None);
+        let mut json = Vec::new();
+        report.write_json(&mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert!(json.contains("\"entries\": 2"));
+        assert!(json.contains("[1, null]"));
+    }
+}
@@ -0,0 +1,120 @@
+use crate::BinaryNumber;
+use crate::criterion::ScanDirection;
+
+struct Node {
+    children: [Option<usize>; 2],
+    count: u64,
+}
+
+impl Node {
+    fn new() -> Node {
+        Node {children: [None, None], count: 0}
+    }
+}
+
+// Binary trie of fixed-width numbers, indexed from the MSB or from the LSB. Each node counts the numbers (with
+// multiplicity) in its subtree, so the amount of numbers sharing any prefix is available without scanning them.
+pub struct BitTrie {
+    nodes: Vec<Node>,
+    positions: Vec<usize>,
+    direction: ScanDirection,
+}
+
+impl BitTrie {
+    const ROOT: usize = 0;
+
+    pub fn new(width: usize, direction: ScanDirection) -> BitTrie {
+        BitTrie {
+            nodes: vec![Node::new()],
+            positions: direction.positions(width),
+            direction,
+        }
+    }
+
+    pub fn direction(&self) -> ScanDirection {
+        self.direction
+    }
+
+    pub fn len(&self) -> u64 {
+        self.nodes[Self::ROOT].count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn insert(&mut self, number: &BinaryNumber, count: u64) {
+        assert_eq!(number.len(), self.positions.len(), "Size mismatch");
+        let mut node = Self::ROOT;
+        self.nodes[node].count += count;
+        for &pos in &self.positions {
+            let bit = number.bit(pos) as usize;
+            node = match self.nodes[node].children[bit] {
+                Some(child) => child,
+                None => {
+                    self.nodes.push(Node::new());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children[bit] = Some(child);
+                    child
+                }
+            };
+            self.nodes[node].count += count;
+        }
+    }
+
+    fn child_count(&self, node: usize, bit: usize) -> u64 {
+        self.nodes[node].children[bit].map_or(0, |child| self.nodes[child].count)
+    }
+
+    // Walks from the first scanned bit down to a single number. At every position `choose` gets the bit position and
+    // the number of remaining entries having a 0 and a 1 there, and returns the bit to follow. When only one of the
+    // two bits is still available it is taken regardless of the choice.
+    pub fn walk<F>(&self, mut choose: F) -> Option<BinaryNumber>
+    where
+        F: FnMut(usize, u64, u64) -> u8,
+    {
+        if self.is_empty() {
+            return None;
+        }
+        let mut number = BinaryNumber::new(self.positions.len());
+        let mut node = Self::ROOT;
+        for &pos in &self.positions {
+            let zeros = self.child_count(node, 0);
+            let ones = self.child_count(node, 1);
+            let bit = match (zeros, ones) {
+                (0, _) => 1,
+                (_, 0) => 0,
+                _ => choose(pos, zeros, ones),
+            };
+            number.set_bit(pos, bit);
+            node = self.nodes[node].children[bit as usize].expect("Missing trie node");
+        }
+        Some(number)
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> BitTrie {
+        let mut trie = BitTrie::new(5, ScanDirection::MsbFirst);
+        for line in ["00100", "11110", "10110", "10111", "10101", "01111",
+                     "00111", "11100", "10000", "11001", "00010", "01010"] {
+            trie.insert(&BinaryNumber::from_binary_str(line).unwrap(), 1);
+        }
+        trie
+    }
+
+    #[test]
+    fn ratings() {
+        let trie = example();
+        assert_eq!(trie.len(), 12);
+        let oxygen = trie.walk(|_, zeros, ones| if ones >= zeros { 1 } else { 0 }).unwrap();
+        let co2 = trie.walk(|_, zeros, ones| if zeros <= ones { 0 } else { 1 }).unwrap();
+        assert_eq!(oxygen.to_string(), "23");
+        assert_eq!(co2.to_string(), "10");
+        assert!(BitTrie::new(5, ScanDirection::MsbFirst).walk(|_, _, _| 0).is_none());
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+use crate::bingo::Board;
+use crate::rules::WinRule;
+
+// Small xorshift generator, enough to shuffle draw orders reproducibly
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // The state must never be zero
+        Rng { state: seed ^ 0x9E37_79B9_7F4A_7C15 | 1 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    // Uniform value in 0..n
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            values.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoardOdds {
+    pub board: usize,
+    // Probability of winning on the earliest winning turn, ties included
+    pub first: f64,
+    // Probability of winning at all before the numbers run out
+    pub wins: f64,
+    // Expected 1-based winning turn, over the draw orders where the board wins
+    pub expected_turn: Option<f64>,
+}
+
+// Accumulates the outcome of many draw orders
+struct Tally {
+    orders: f64,
+    first: Vec<f64>,
+    wins: Vec<f64>,
+    turns: Vec<f64>,
+}
+
+impl Tally {
+    fn new(nboards: usize) -> Tally {
+        Tally {
+            orders: 0.0,
+            first: vec![0.0; nboards],
+            wins: vec![0.0; nboards],
+            turns: vec![0.0; nboards],
+        }
+    }
+
+    fn add(&mut self, turns: &[Option<f64>]) {
+        self.orders += 1.0;
+        let earliest = turns.iter().flatten().fold(f64::INFINITY, |a, &b| a.min(b));
+        for (b, turn) in turns.iter().enumerate() {
+            if let Some(turn) = *turn {
+                self.wins[b] += 1.0;
+                self.turns[b] += turn;
+                if turn == earliest {
+                    self.first[b] += 1.0;
+                }
+            }
+        }
+    }
+
+    fn odds(&self) -> Vec<BoardOdds> {
+        (0..self.first.len()).map(|b| BoardOdds {
+            board: b,
+            first: self.first[b] / self.orders,
+            wins: self.wins[b] / self.orders,
+            expected_turn: if self.wins[b] > 0.0 { Some(self.turns[b] / self.wins[b]) } else { None },
+        }).collect()
+    }
+}
+
+// Plays the draw order on fresh copies of the boards, returning the 1-based turn at which each board wins
+fn winning_turns(boards: &[Board], rule: &dyn WinRule, order: &[u8]) -> Vec<Option<usize>> {
+    let mut boards = boards.to_vec();
+    let mut turns = vec![None; boards.len()];
+    let mut remaining = boards.len();
+    for (turn, &number) in order.iter().enumerate() {
+        for (b, board) in boards.iter_mut().enumerate() {
+            if !board.wins() && board.mark_if_present(number, rule) && board.wins() {
+                turns[b] = Some(turn + 1);
+                remaining -= 1;
+            }
+        }
+        if remaining == 0 {
+            break;
+        }
+    }
+    turns
+}
+
+// Estimates the odds by playing `trials` random orders of all the numbers in `universe`
+pub fn monte_carlo(boards: &[Board], rule: &dyn WinRule, universe: &[u8], trials: usize, seed: u64) -> Vec<BoardOdds> {
+    let mut rng = Rng::new(seed);
+    let mut order = Vec::from(universe);
+    let mut tally = Tally::new(boards.len());
+    for _ in 0..trials {
+        rng.shuffle(&mut order);
+        let turns: Vec<Option<f64>> = winning_turns(boards, rule, &order).iter()
+            .map(|turn| turn.map(|t| t as f64))
+            .collect();
+        tally.add(&turns);
+    }
+    tally.odds()
+}
+
+// Computes the odds exactly by going through every order of the numbers that appear on some board. The other
+// numbers only delay the game: with n numbers drawn of which m matter, the k-th relevant draw happens on average
+// at turn k * (n + 1) / (m + 1). Returns None when there are more than `max_relevant` relevant numbers.
+pub fn exact(boards: &[Board], rule: &dyn WinRule, universe: &[u8], max_relevant: usize) -> Option<Vec<BoardOdds>> {
+    let on_boards: HashSet<u8> = boards.iter().flat_map(|board| board.cells().iter().map(|cell| cell.number)).collect();
+    let mut relevant: Vec<u8> = universe.iter().copied().filter(|number| on_boards.contains(number)).collect();
+    relevant.sort_unstable();
+    relevant.dedup();
+    if relevant.len() > max_relevant {
+        return None;
+    }
+    let scale = (universe.len() + 1) as f64 / (relevant.len() + 1) as f64;
+    let mut tally = Tally::new(boards.len());
+    let mut record = |order: &[u8]| {
+        let turns: Vec<Option<f64>> = winning_turns(boards, rule, order).iter()
+            .map(|turn| turn.map(|k| k as f64 * scale))
+            .collect();
+        tally.add(&turns);
+    };
+    // Heap's algorithm
+    let n = relevant.len();
+    let mut counters = vec![0; n];
+    record(&relevant);
+    let mut i = 1;
+    while i < n {
+        if counters[i] < i {
+            let j = if i % 2 == 0 { 0 } else { counters[i] };
+            relevant.swap(j, i);
+            record(&relevant);
+            counters[i] += 1;
+            i = 1;
+        } else {
+            counters[i] = 0;
+            i += 1;
+        }
+    }
+    Some(tally.odds())
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::AnyOf;
+
+    #[test]
+    fn shuffle_is_reproducible() {
+        let mut a: Vec<u8> = (0..50).collect();
+        let mut b = a.clone();
+        Rng::new(42).shuffle(&mut a);
+        Rng::new(42).shuffle(&mut b);
+        assert_eq!(a, b);
+        let mut sorted = a.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..50).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn exact_single_cell_boards() {
+        // Two 1x1 boards and one extra number: each board wins first half of the time
+        let boards = vec![Board::from_rows(&[vec![1]]), Board::from_rows(&[vec![2]])];
+        let odds = exact(&boards, &AnyOf::standard(), &[1, 2, 3], 8).unwrap();
+        for board_odds in &odds {
+            assert_eq!(board_odds.first, 0.5);
+            assert_eq!(board_odds.wins, 1.0);
+            // Each number is on average drawn on turn 2 out of 3
+            assert_eq!(board_odds.expected_turn, Some(2.0));
+        }
+        assert!(exact(&boards, &AnyOf::standard(), &[1, 2, 3], 1).is_none());
+    }
+
+    #[test]
+    fn monte_carlo_close_to_exact() {
+        // The first board wins with either 1 or 2, the second one needs a row or a column
+        let boards = vec![
+            Board::from_rows(&[vec![1, 2]]),
+            Board::from_rows(&[vec![1, 2], vec![3, 4]]),
+        ];
+        let rule = AnyOf::standard();
+        let universe = [1, 2, 3, 4, 5, 6];
+        let exact = exact(&boards, &rule, &universe, 8).unwrap();
+        let estimate = monte_carlo(&boards, &rule, &universe, 20000, 7);
+        for (e, m) in exact.iter().zip(&estimate) {
+            assert!((e.first - m.first).abs() < 0.02, "{:?} vs {:?}", e, m);
+            assert!((e.expected_turn.unwrap() - m.expected_turn.unwrap()).abs() < 0.1, "{:?} vs {:?}", e, m);
+        }
+        // The second board is first only if 3 and 4 come first, and ties if 3-1 or 4-2 do
+        assert!((exact[0].first - 5.0 / 6.0).abs() < 1e-12);
+        assert!((exact[1].first - 1.0 / 3.0).abs() < 1e-12);
+    }
+}
@@ -1,10 +1,5 @@
-use std::io::{BufReader, BufRead, Error, ErrorKind};
-use std::fmt::{format, Formatter};
-use std::fs::File;
-use std::ptr::write;
-
-const BOARD_ROWS: usize = 5;
-const BOARD_COLS: usize = 5;
+use std::fmt::Formatter;
+use crate::rules::WinRule;
 
 #[derive(Copy, Clone)]
 pub struct Cell {
@@ -18,75 +13,76 @@ impl Cell {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct Board {
-    cells: [Cell; BOARD_ROWS * BOARD_COLS],
+    rows: usize,
+    cols: usize,
+    cells: Vec<Cell>,
     winning_number: Option<u8>,
 }
 
 impl Board {
-    pub fn new() -> Board {
+    pub fn new(rows: usize, cols: usize) -> Board {
         Board {
-            cells: [Cell::new(0); BOARD_ROWS * BOARD_COLS],
+            rows,
+            cols,
+            cells: vec![Cell::new(0); rows * cols],
             winning_number: None,
         }
     }
 
-    pub fn read(reader: &mut BufReader<File>) -> Result<Board, Error> {
-        let mut board = Board::new();
-        for i in 0..BOARD_ROWS {
-            // Read line
-            let mut line = String::new();
-            while line.is_empty() {
-                match reader.read_line(&mut line) {
-                    Ok(bytes) => {
-                        if bytes == 0 {
-                            break;
-                        }
-                    },
-                    Err(e) => return Err(e),
-                }
-                line = String::from(line.trim());
-            }
-            // Parse numbers
-            let mut columns = 0;
-            for (j, number) in line.trim().split_whitespace().enumerate() {
-                let number: u8 = number.parse().expect("Could not parse number");
-                board.get_cell_mut(i as u8, j as u8).number = number;
-                columns = j + 1;
-            }
-            if columns < BOARD_COLS {
-                return Err(Error::new(ErrorKind::InvalidInput, "Not enough input numbers"))
+    pub fn from_rows(numbers: &[Vec<u8>]) -> Board {
+        let cols = numbers.first().map_or(0, |row| row.len());
+        assert!(numbers.iter().all(|row| row.len() == cols), "Rows must have the same length");
+        let mut board = Board::new(numbers.len(), cols);
+        for (i, row) in numbers.iter().enumerate() {
+            for (j, &number) in row.iter().enumerate() {
+                board.get_cell_mut(i, j).number = number;
             }
         }
-        Ok(board)
+        board
     }
 
-    fn check_access(row: u8, col: u8) {
-        assert!((row as usize) < BOARD_ROWS, "Invalid row {}", row);
-        assert!((col as usize) < BOARD_ROWS, "Invalid column {}", col);
+    pub fn rows(&self) -> usize {
+        self.rows
     }
 
-    pub fn get_cell(&self, row: u8, col: u8) -> &Cell {
-        Board::check_access(row, col);
-        &self.cells[(row as usize) * BOARD_ROWS + col as usize]
+    pub fn cols(&self) -> usize {
+        self.cols
     }
 
-    pub fn get_cell_mut(&mut self, row: u8, col: u8) -> &mut Cell {
-        Board::check_access(row, col);
-        &mut self.cells[(row as usize) * BOARD_ROWS + col as usize]
+    fn check_access(&self, row: usize, col: usize) {
+        assert!(row < self.rows, "Invalid row {}", row);
+        assert!(col < self.cols, "Invalid column {}", col);
     }
 
-    pub fn find_number(&self, num: u8) -> Option<&Cell> {
-        for cell in &self.cells {
-            if cell.number == num {
-                return Some(&cell);
-            }
-        }
-        None
+    pub fn get_cell(&self, row: usize, col: usize) -> &Cell {
+        self.check_access(row, col);
+        &self.cells[row * self.cols + col]
+    }
+
+    pub fn get_cell_mut(&mut self, row: usize, col: usize) -> &mut Cell {
+        self.check_access(row, col);
+        &mut self.cells[row * self.cols + col]
+    }
+
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    // Marks the cell at the given position in row-major order, returning whether it was not marked before
+    pub fn mark_cell(&mut self, index: usize) -> bool {
+        let cell = &mut self.cells[index];
+        let newly_marked = !cell.marked;
+        cell.marked = true;
+        newly_marked
     }
 
-    pub fn find_number_mut(&mut self, num: u8) -> Option<&mut Cell> {
+    pub fn set_winning_number(&mut self, num: u8) {
+        self.winning_number = Some(num);
+    }
+
+    fn find_number_mut(&mut self, num: u8) -> Option<&mut Cell> {
         for cell in &mut self.cells {
             if cell.number == num {
                 return Some(&mut *cell);
@@ -95,10 +91,10 @@ impl Board {
         None
     }
 
-    pub fn mark_if_present(&mut self, num: u8) -> bool {
+    pub fn mark_if_present(&mut self, num: u8, rule: &dyn WinRule) -> bool {
         if let Some(cell) = self.find_number_mut(num) {
             cell.marked = true;
-            if self.check_winning() {
+            if rule.wins(self) {
                 self.winning_number = Some(num);
             }
             return true;
@@ -114,40 +110,10 @@ impl Board {
         self.winning_number
     }
 
-    fn check_winning(&self) -> bool {
-        // Check if any row is complete
-        for i in 0..BOARD_ROWS as u8 {
-            let mut row_marked = true;
-            for j in 0..BOARD_COLS as u8 {
-                if !self.get_cell(i, j).marked {
-                    row_marked = false;
-                    break;
-                }
-            };
-            if row_marked {
-                return true;
-            }
-        }
-        // Check if any column is complete
-        for j in 0..BOARD_COLS as u8 {
-            let mut column_marked = true;
-            for i in 0..BOARD_ROWS as u8 {
-                if !self.get_cell(i, j).marked {
-                    column_marked = false;
-                    break;
-                }
-            };
-            if column_marked {
-                return true;
-            }
-        }
-        false
-    }
-
     pub fn score(&self) -> Option<u64> {
         if let Some(winning_number) = self.winning_number {
             let mut score = 0u64;
-            for cell in self.cells {
+            for cell in &self.cells {
                 if !cell.marked {
                     score += cell.number as u64;
                 }
@@ -162,8 +128,8 @@ impl Board {
 
 impl std::fmt::Display for Board {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        for i in 0..BOARD_ROWS as u8 {
-            for j in 0..BOARD_COLS as u8{
+        for i in 0..self.rows {
+            for j in 0..self.cols {
                 let cell = self.get_cell(i, j);
                 if cell.marked {
                     write!(f, "{:>6}", format!("({:>2})", cell.number))?;
@@ -175,4 +141,31 @@ impl std::fmt::Display for Board {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::AnyOf;
+
+    #[test]
+    fn rectangular_rows_and_columns() {
+        let rule = AnyOf::standard();
+        let rows = vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10, 11, 12]];
+        // Complete the last column
+        let mut board = Board::from_rows(&rows);
+        for number in [4, 8] {
+            board.mark_if_present(number, &rule);
+            assert!(!board.wins());
+        }
+        board.mark_if_present(12, &rule);
+        assert_eq!(board.winning_number(), Some(12));
+        assert_eq!(board.score(), Some(12 * (78 - 4 - 8 - 12)));
+        // Complete the middle row
+        let mut board = Board::from_rows(&rows);
+        for number in [5, 6, 7, 8] {
+            board.mark_if_present(number, &rule);
+        }
+        assert_eq!(board.winning_number(), Some(8));
+    }
+}
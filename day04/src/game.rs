@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use crate::bingo::Board;
+use crate::rules::WinRule;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Win {
+    pub board: usize,
+    // Position of the winning number in the draw sequence
+    pub draw: usize,
+    pub number: u8,
+    pub score: u64,
+}
+
+// Outcome of a whole game: winners in finishing order, boards winning on the same draw by index
+#[derive(Debug, PartialEq)]
+pub struct Standings {
+    pub wins: Vec<Win>,
+    pub never_won: Vec<usize>,
+}
+
+impl Standings {
+    pub fn first(&self) -> Option<&Win> {
+        self.wins.first()
+    }
+
+    pub fn last(&self) -> Option<&Win> {
+        self.wins.last()
+    }
+}
+
+// Winning patterns of a board shape, as cell indices in row-major order
+struct Layout {
+    patterns: Vec<Vec<usize>>,
+    // For each cell, the patterns it belongs to
+    cell_patterns: Vec<Vec<usize>>,
+}
+
+impl Layout {
+    fn new(rows: usize, cols: usize, rule: &dyn WinRule) -> Layout {
+        let patterns: Vec<Vec<usize>> = rule.patterns(rows, cols).iter()
+            .map(|pattern| pattern.iter().map(|&(i, j)| i * cols + j).collect())
+            .collect();
+        let mut cell_patterns = vec![Vec::new(); rows * cols];
+        for (p, pattern) in patterns.iter().enumerate() {
+            for &cell in pattern {
+                cell_patterns[cell].push(p);
+            }
+        }
+        Layout { patterns, cell_patterns }
+    }
+}
+
+// Plays many boards at once. Every number knows the cells holding it and every board counts the marked cells of
+// each winning pattern, so a draw only touches the cells it marks. Boards stop being marked once they win.
+pub struct Game {
+    boards: Vec<Board>,
+    layouts: Vec<Layout>,
+    board_layouts: Vec<usize>,
+    // For each number, the (board, cell) pairs holding it
+    index: Vec<Vec<(usize, usize)>>,
+    // For each board, the number of marked cells in each of its patterns
+    marks: Vec<Vec<usize>>,
+}
+
+impl Game {
+    pub fn new(boards: Vec<Board>, rule: &dyn WinRule) -> Game {
+        let mut layouts = Vec::new();
+        let mut shapes = HashMap::new();
+        let mut board_layouts = Vec::with_capacity(boards.len());
+        let mut index = vec![Vec::new(); u8::MAX as usize + 1];
+        let mut marks = Vec::with_capacity(boards.len());
+        for (b, board) in boards.iter().enumerate() {
+            let shape = (board.rows(), board.cols());
+            let layout = *shapes.entry(shape).or_insert_with(|| {
+                layouts.push(Layout::new(shape.0, shape.1, rule));
+                layouts.len() - 1
+            });
+            board_layouts.push(layout);
+            marks.push(vec![0; layouts[layout].patterns.len()]);
+            for (c, cell) in board.cells().iter().enumerate() {
+                index[cell.number as usize].push((b, c));
+            }
+        }
+        let mut game = Game { boards, layouts, board_layouts, index, marks };
+        // Account for cells marked before the game started
+        for b in 0..game.boards.len() {
+            for c in 0..game.boards[b].cells().len() {
+                let cell = game.boards[b].cells()[c];
+                if cell.marked {
+                    game.count_mark(b, c, cell.number);
+                }
+            }
+        }
+        game
+    }
+
+    pub fn boards(&self) -> &[Board] {
+        &self.boards
+    }
+
+    fn count_mark(&mut self, b: usize, c: usize, number: u8) -> bool {
+        let layout = &self.layouts[self.board_layouts[b]];
+        let mut wins = false;
+        for &p in &layout.cell_patterns[c] {
+            self.marks[b][p] += 1;
+            wins |= self.marks[b][p] == layout.patterns[p].len();
+        }
+        if wins && !self.boards[b].wins() {
+            self.boards[b].set_winning_number(number);
+            return true;
+        }
+        false
+    }
+
+    // Marks the number on every board holding it and returns the boards winning because of it
+    pub fn draw(&mut self, number: u8) -> Vec<usize> {
+        let mut winners = Vec::new();
+        for i in 0..self.index[number as usize].len() {
+            let (b, c) = self.index[number as usize][i];
+            if self.boards[b].wins() || !self.boards[b].mark_cell(c) {
+                continue;
+            }
+            if self.count_mark(b, c, number) {
+                winners.push(b);
+            }
+        }
+        winners
+    }
+
+    // Draws the whole sequence, stopping early once every board has won
+    pub fn play(&mut self, numbers: &[u8]) -> Standings {
+        let mut wins = Vec::new();
+        for (draw, &drawn) in numbers.iter().enumerate() {
+            if wins.len() == self.boards.len() {
+                break;
+            }
+            let mut winners = self.draw(drawn);
+            winners.sort_unstable();
+            for board in winners {
+                let number = self.boards[board].winning_number().expect("Winner without a winning number");
+                let score = self.boards[board].score().expect("Winner without a score");
+                wins.push(Win { board, draw, number, score });
+            }
+        }
+        let never_won = (0..self.boards.len()).filter(|&b| !self.boards[b].wins()).collect();
+        Standings { wins, never_won }
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{AnyOf, Diagonals};
+
+    #[test]
+    fn matches_single_boards() {
+        let boards = vec![
+            Board::from_rows(&[vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]),
+            Board::from_rows(&[vec![9, 8], vec![7, 6], vec![5, 4]]),
+            Board::from_rows(&[vec![5, 1, 9], vec![2, 3, 4], vec![6, 7, 8]]),
+        ];
+        let rule = AnyOf::new(vec![Box::new(AnyOf::standard()), Box::new(Diagonals)]);
+        let mut game = Game::new(boards.clone(), &rule);
+        let mut reference = boards;
+        for number in [5, 9, 2, 4, 1, 7, 3, 6, 8] {
+            let winners = game.draw(number);
+            for (b, board) in reference.iter_mut().enumerate() {
+                let won_before = board.wins();
+                if !won_before {
+                    board.mark_if_present(number, &rule);
+                }
+                assert_eq!(winners.contains(&b), !won_before && board.wins(), "Board {} on draw {}", b, number);
+                assert_eq!(game.boards()[b].score(), board.score());
+            }
+        }
+        assert!(game.boards().iter().all(|board| board.wins()));
+    }
+
+    #[test]
+    fn finishing_order() {
+        let boards = vec![
+            Board::from_rows(&[vec![1, 2], vec![3, 4]]),
+            Board::from_rows(&[vec![5, 6], vec![7, 8]]),
+            Board::from_rows(&[vec![2, 9], vec![4, 10]]),
+            Board::from_rows(&[vec![11, 12], vec![13, 14]]),
+        ];
+        let mut game = Game::new(boards, &AnyOf::standard());
+        let standings = game.play(&[5, 2, 4, 7, 1, 3, 8]);
+        assert_eq!(standings.wins, vec![
+            Win { board: 0, draw: 2, number: 4, score: 4 * (1 + 3) },
+            Win { board: 2, draw: 2, number: 4, score: 4 * (9 + 10) },
+            Win { board: 1, draw: 3, number: 7, score: 7 * (6 + 8) },
+        ]);
+        assert_eq!(standings.never_won, vec![3]);
+        assert_eq!(standings.first().map(|win| win.board), Some(0));
+        assert_eq!(standings.last().map(|win| win.board), Some(1));
+    }
+
+    #[test]
+    fn winners_are_frozen() {
+        let mut game = Game::new(vec![Board::from_rows(&[vec![1, 2], vec![3, 4]])], &AnyOf::standard());
+        assert!(game.draw(1).is_empty());
+        assert_eq!(game.draw(2), vec![0]);
+        assert!(game.draw(3).is_empty());
+        assert_eq!(game.boards()[0].winning_number(), Some(2));
+        assert_eq!(game.boards()[0].score(), Some(2 * (3 + 4)));
+    }
+}
@@ -1,114 +1,139 @@
-use std::collections::HashSet;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::analysis::BoardOdds;
 use crate::bingo::Board;
+use crate::game::{Game, Standings, Win};
+use crate::rules::{AnyOf, Blackout, Columns, Custom, Diagonals, FourCorners, Rows, WinRule};
+use crate::solver::Goal;
 
+mod analysis;
 mod bingo;
+mod game;
+mod parser;
+mod rules;
+mod solver;
 
-fn read_generated_numbers(reader: &mut BufReader<File>) -> Vec<u8> {
-    let mut line = String::new();
-    reader.read_line(&mut line).expect("Could not read generated numbers");
-    // Count number of inputs
-    let mut nnumbers = 1;
-    for c in line.chars() {
-        if c == ',' {
-            nnumbers += 1;
+fn read_input(fname: &str) -> (Vec<u8>, Vec<Board>) {
+    let input = std::fs::read_to_string(fname).expect("Could not read file");
+    match parser::parse_str(&input) {
+        Ok(input) => (input.numbers, input.boards),
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{}: {}", fname, error);
+            }
+            std::process::exit(1);
         }
     }
-    let nnumbers = nnumbers;
-    let mut numbers = Vec::with_capacity(nnumbers);
-    for number in line.trim().split(",") {
-        let number: u8 = number.parse().expect("Could not read number");
-        numbers.push(number);
-    }
-    numbers
 }
 
-fn print_boards(boards: &Vec<Board>) {
-    for (i, board) in boards.iter().enumerate() {
-        println!("Board {}:\n{}\n", i, board);
+fn print_win(title: &str, game: &Game, win: Option<&Win>) {
+    if let Some(win) = win {
+        println!("{} (board {}, draw {}):\n{}Winning number: {}\tScore: {}", title, win.board, win.draw,
+                 game.boards()[win.board], win.number, win.score);
+    } else {
+        println!("There is no winner");
     }
 }
 
-fn part1() {
+fn part1(game: &Game, standings: &Standings) {
     println!("{:-<80}", "Part 1 ");
-    let f = File::open("data.txt").expect("Could not open file");
-    let mut reader = BufReader::new(f);
-    let numbers = read_generated_numbers(&mut reader);
-
-    // Read all available boards
-    let mut boards = Vec::new();
-    loop {
-        match Board::read(&mut reader) {
-            Ok(board) => boards.push(board),
-            Err(_) => break,
-        }
-    }
-    // Print info about boards
-    println!("Found {} boards", boards.len());
-    // Find winner board
-    let mut winner: Option<usize> = None;
-    'extraction: for number in numbers {
-        for board in &mut boards {
-            board.mark_if_present(number);
-        }
-        for (i, board) in boards.iter().enumerate() {
-            if board.wins() {
-                winner = Some(i);
-                break 'extraction;
-            }
-        }
-    }
-    if let Some(index) = winner {
-        let winner = &boards[index];
-        println!("Winner board:\n{}Score: {}", winner, winner.score()
-                                                              .expect("Could not extract winner score"));
-    } else {
-        print!("There is no winner");
-    }
+    print_win("Winner board", game, standings.first());
 }
 
-fn part2() {
+fn part2(game: &Game, standings: &Standings) {
     println!("{:-<80}", "Part 2 ");
-    let f = File::open("data.txt").expect("Could not open file");
-    let mut reader = BufReader::new(f);
-    let numbers = read_generated_numbers(&mut reader);
+    print_win("Last winning board", game, standings.last());
+    if !standings.never_won.is_empty() {
+        println!("Boards that never won: {:?}", standings.never_won);
+    }
+}
 
-    // Read all available boards
-    let mut boards = Vec::new();
-    loop {
-        match Board::read(&mut reader) {
-            Ok(board) => boards.push(board),
-            Err(_) => break,
-        }
+// Estimates which board is most likely to win first if the numbers were drawn in a random order
+fn odds(boards: &[Board], rule: &dyn WinRule, numbers: &[u8]) {
+    println!("{:-<80}", "Odds ");
+    const TRIALS: usize = 200;
+    const SEED: u64 = 2021;
+    const MAX_EXACT: usize = 8;
+    let mut universe = Vec::from(numbers);
+    universe.sort_unstable();
+    universe.dedup();
+    let mut odds = match analysis::exact(boards, rule, &universe, MAX_EXACT) {
+        Some(odds) => odds,
+        None => analysis::monte_carlo(boards, rule, &universe, TRIALS, SEED),
+    };
+    odds.sort_by(|a, b| b.first.total_cmp(&a.first));
+    for BoardOdds { board, first, wins, expected_turn } in odds.iter().take(3) {
+        println!("Board {}: first with p = {:.3}, wins with p = {:.3}, expected turn {:.1}",
+                 board, first, wins, expected_turn.unwrap_or(f64::NAN));
     }
-    // Print info about boards
-    println!("Found {} boards", boards.len());
+}
 
-    // Find last winner board
-    for number in numbers {
-        if boards.len() > 1 {
-            for board in &mut boards {
-                board.mark_if_present(number);
-            }
-            boards.retain(|board| !board.wins());
-        } else {
-            boards[0].mark_if_present(number);
-            if boards[0].wins() {
-                break;
-            }
-        }
+// Looks for the quickest draw order making the requested board win first or last, using only the drawn numbers
+fn adversary(boards: &[Board], rule: &dyn WinRule, numbers: &[u8], (goal, target, bound): Search) {
+    println!("{:-<80}", "Adversary ");
+    if target >= boards.len() {
+        println!("There is no board {}", target);
+        return;
     }
-    let mut last_board = boards[0];
-    boards.clear();
-    if last_board.wins() {
-        println!("Last winning board:\n{}Score: {}", last_board, last_board.score().expect("No winner score"));
-    } else {
-        println!("No last board won");
+    let wanted = match goal {
+        Goal::First => "first",
+        Goal::Last => "last",
+    };
+    let outcome = solver::fastest_win(boards, rule, target, goal, numbers, bound);
+    match outcome.plan {
+        Some(plan) => println!("Board {} wins {} after {} draws {:?}\tScore: {}", target, wanted,
+                               plan.draws.len(), plan.draws, plan.score),
+        None if outcome.exhaustive => println!("Board {} cannot win {} within {} draws", target, wanted, bound),
+        None => println!("No draw order found making board {} win {} within {} draws", target, wanted, bound),
+    }
+    if !outcome.exhaustive {
+        println!("Search stopped early, shorter draw orders may exist");
+    }
+}
+
+// Picks the win rule: a rule name or a custom pattern such as "x...x/...../x...x"
+fn read_rule(arg: Option<&str>) -> Box<dyn WinRule> {
+    match arg {
+        None | Some("standard") => Box::new(AnyOf::standard()),
+        Some("rows") => Box::new(Rows),
+        Some("columns") => Box::new(Columns),
+        Some("diagonals") => Box::new(Diagonals),
+        Some("corners") => Box::new(FourCorners),
+        Some("blackout") => Box::new(Blackout),
+        Some(grid) => Box::new(Custom::from_grid(grid).expect("Invalid win rule")),
     }
 }
 
+// Goal, board index and maximum number of draws of an adversarial search
+type Search = (Goal, usize, usize);
+
+// Command line: [rule] [--first|--last <board> <bound>]
+fn read_args() -> (Box<dyn WinRule>, Option<Search>) {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let flag = args.iter().position(|arg| arg == "--first" || arg == "--last");
+    let rule = read_rule(args.first().filter(|_| flag != Some(0)).map(String::as_str));
+    let search = flag.map(|i| {
+        let goal = if args[i] == "--first" { Goal::First } else { Goal::Last };
+        let value = |k: usize| -> usize {
+            args.get(i + k).and_then(|arg| arg.parse().ok()).expect("Expected a board index and a bound")
+        };
+        (goal, value(1), value(2))
+    });
+    (rule, search)
+}
+
 fn main() {
-    part1();
-    part2();
+    let (rule, search) = read_args();
+    let (numbers, boards) = read_input("data.txt");
+    // Print info about boards
+    if let Some(board) = boards.first() {
+        println!("Found {} boards of size {}x{}", boards.len(), board.rows(), board.cols());
+    }
+    odds(&boards, rule.as_ref(), &numbers);
+    if let Some(search) = search {
+        adversary(&boards, rule.as_ref(), &numbers, search);
+    }
+    // Play the whole game once, both parts only look at the results
+    let mut game = Game::new(boards, rule.as_ref());
+    let standings = game.play(&numbers);
+    part1(&game, &standings);
+    part2(&game, &standings);
 }
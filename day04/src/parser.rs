@@ -0,0 +1,220 @@
+use std::collections::HashSet;
+use std::fmt::Formatter;
+use std::io::BufRead;
+use crate::bingo::Board;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseErrorKind {
+    Io(String),
+    MissingNumbers,
+    InvalidNumber(String),
+    OutOfRange(String),
+    RaggedRow { expected: usize, found: usize },
+    DuplicateNumber(u8),
+}
+
+// Problem found in the input, with its 1-based line and the index of the board it belongs to, if any
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub board: Option<usize>,
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "Line {}", self.line)?;
+        if let Some(board) = self.board {
+            write!(f, " (board {})", board)?;
+        }
+        match &self.kind {
+            ParseErrorKind::Io(message) => write!(f, ": {}", message),
+            ParseErrorKind::MissingNumbers => write!(f, ": missing drawn numbers"),
+            ParseErrorKind::InvalidNumber(number) => write!(f, ": invalid number '{}'", number),
+            ParseErrorKind::OutOfRange(number) => write!(f, ": number {} out of range 0-{}", number, u8::MAX),
+            ParseErrorKind::RaggedRow { expected, found } =>
+                write!(f, ": row has {} numbers instead of {}", found, expected),
+            ParseErrorKind::DuplicateNumber(number) => write!(f, ": number {} appears twice", number),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone)]
+pub struct Input {
+    pub numbers: Vec<u8>,
+    pub boards: Vec<Board>,
+}
+
+fn parse_number(number: &str) -> Result<u8, ParseErrorKind> {
+    number.parse().map_err(|_| {
+        // Anything that is an integer at all is only too large or negative
+        if number.parse::<i128>().is_ok() {
+            ParseErrorKind::OutOfRange(String::from(number))
+        } else {
+            ParseErrorKind::InvalidNumber(String::from(number))
+        }
+    })
+}
+
+// Collects the lines of a board and checks them as they come
+struct BoardBuilder {
+    index: usize,
+    rows: Vec<Vec<u8>>,
+    seen: HashSet<u8>,
+}
+
+impl BoardBuilder {
+    fn new(index: usize) -> BoardBuilder {
+        BoardBuilder { index, rows: Vec::new(), seen: HashSet::new() }
+    }
+
+    fn error(&self, line: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError { board: Some(self.index), line, kind }
+    }
+
+    fn add_row(&mut self, line: usize, text: &str, errors: &mut Vec<ParseError>) {
+        let mut row = Vec::new();
+        let mut valid = true;
+        for number in text.split_whitespace() {
+            match parse_number(number) {
+                Ok(number) => {
+                    if !self.seen.insert(number) {
+                        errors.push(self.error(line, ParseErrorKind::DuplicateNumber(number)));
+                    }
+                    row.push(number);
+                }
+                Err(kind) => {
+                    errors.push(self.error(line, kind));
+                    valid = false;
+                }
+            }
+        }
+        // Rows with bad numbers have an unknown length
+        if let Some(first) = self.rows.first() {
+            if valid && row.len() != first.len() {
+                errors.push(self.error(line, ParseErrorKind::RaggedRow { expected: first.len(), found: row.len() }));
+            }
+        }
+        self.rows.push(row);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    fn build(&self) -> Option<Board> {
+        let cols = self.rows.first()?.len();
+        if self.rows.iter().all(|row| row.len() == cols) {
+            Some(Board::from_rows(&self.rows))
+        } else {
+            None
+        }
+    }
+}
+
+// Reads the drawn numbers, comma separated on the first line, followed by boards separated by blank lines. Every
+// problem found is reported, not just the first one.
+pub fn parse<R: BufRead>(reader: R) -> Result<Input, Vec<ParseError>> {
+    let mut errors = Vec::new();
+    let mut numbers = None;
+    let mut boards = Vec::new();
+    let mut current = BoardBuilder::new(0);
+    for (i, line) in reader.lines().enumerate() {
+        let line_number = i + 1;
+        let line = match line {
+            Ok(line) => line,
+            Err(error) => {
+                errors.push(ParseError { board: None, line: line_number, kind: ParseErrorKind::Io(error.to_string()) });
+                break;
+            }
+        };
+        let line = line.trim();
+        if numbers.is_none() {
+            if line.is_empty() {
+                errors.push(ParseError { board: None, line: line_number, kind: ParseErrorKind::MissingNumbers });
+            }
+            let mut drawn = Vec::new();
+            for number in line.split(',').map(str::trim).filter(|_| !line.is_empty()) {
+                match parse_number(number) {
+                    Ok(number) => drawn.push(number),
+                    Err(kind) => errors.push(ParseError { board: None, line: line_number, kind }),
+                }
+            }
+            numbers = Some(drawn);
+        } else if line.is_empty() {
+            if !current.is_empty() {
+                boards.extend(current.build());
+                current = BoardBuilder::new(current.index + 1);
+            }
+        } else {
+            current.add_row(line_number, line, &mut errors);
+        }
+    }
+    if !current.is_empty() {
+        boards.extend(current.build());
+    }
+    if numbers.is_none() && errors.is_empty() {
+        errors.push(ParseError { board: None, line: 1, kind: ParseErrorKind::MissingNumbers });
+    }
+    if errors.is_empty() {
+        Ok(Input { numbers: numbers.unwrap_or_default(), boards })
+    } else {
+        Err(errors)
+    }
+}
+
+pub fn parse_str(input: &str) -> Result<Input, Vec<ParseError>> {
+    parse(input.as_bytes())
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(input: &str) -> Vec<(Option<usize>, usize, ParseErrorKind)> {
+        parse_str(input).err().expect("Input should be invalid").into_iter()
+            .map(|error| (error.board, error.line, error.kind))
+            .collect()
+    }
+
+    #[test]
+    fn valid_input() {
+        let input = parse_str("7,4,9\n\n 1 2\n3  4\n\n\n5 6 7\n8 9 10\n").unwrap();
+        assert_eq!(input.numbers, vec![7, 4, 9]);
+        assert_eq!(input.boards.len(), 2);
+        assert_eq!((input.boards[0].rows(), input.boards[0].cols()), (2, 2));
+        assert_eq!((input.boards[1].rows(), input.boards[1].cols()), (2, 3));
+        assert_eq!(input.boards[1].get_cell(1, 2).number, 10);
+        // No boards at all is fine
+        assert!(parse_str("1,2").unwrap().boards.is_empty());
+    }
+
+    #[test]
+    fn drawn_numbers() {
+        assert_eq!(kinds(""), vec![(None, 1, ParseErrorKind::MissingNumbers)]);
+        assert_eq!(kinds("\n1 2\n"), vec![(None, 1, ParseErrorKind::MissingNumbers)]);
+        assert_eq!(kinds("1,x,300,,-2"), vec![
+            (None, 1, ParseErrorKind::InvalidNumber(String::from("x"))),
+            (None, 1, ParseErrorKind::OutOfRange(String::from("300"))),
+            (None, 1, ParseErrorKind::InvalidNumber(String::new())),
+            (None, 1, ParseErrorKind::OutOfRange(String::from("-2"))),
+        ]);
+    }
+
+    #[test]
+    fn board_problems() {
+        let input = "1,2\n\n1 2 3\n4 5\n6 7 8 9\n\n1 2\n2 256\n\n1 a\n";
+        assert_eq!(kinds(input), vec![
+            (Some(0), 4, ParseErrorKind::RaggedRow { expected: 3, found: 2 }),
+            (Some(0), 5, ParseErrorKind::RaggedRow { expected: 3, found: 4 }),
+            (Some(1), 8, ParseErrorKind::DuplicateNumber(2)),
+            (Some(1), 8, ParseErrorKind::OutOfRange(String::from("256"))),
+            (Some(2), 10, ParseErrorKind::InvalidNumber(String::from("a"))),
+        ]);
+        let error = parse_str(input).err().unwrap().remove(2);
+        assert_eq!(error.to_string(), "Line 8 (board 1): number 2 appears twice");
+    }
+}
@@ -0,0 +1,186 @@
+use crate::bingo::Board;
+
+// Cells, as (row, column), that must all be marked to win
+pub type Pattern = Vec<(usize, usize)>;
+
+pub trait WinRule {
+    // All the ways of winning on a board of the given size
+    fn patterns(&self, rows: usize, cols: usize) -> Vec<Pattern>;
+
+    fn wins(&self, board: &Board) -> bool {
+        self.patterns(board.rows(), board.cols()).iter()
+            .any(|pattern| pattern.iter().all(|&(row, col)| board.get_cell(row, col).marked))
+    }
+}
+
+pub struct Rows;
+pub struct Columns;
+// Both diagonals, only for square boards
+pub struct Diagonals;
+pub struct FourCorners;
+// Every cell of the board
+pub struct Blackout;
+
+pub struct Custom {
+    patterns: Vec<Pattern>,
+}
+
+// Wins as soon as any of the inner rules does
+pub struct AnyOf {
+    rules: Vec<Box<dyn WinRule>>,
+}
+
+impl WinRule for Rows {
+    fn patterns(&self, rows: usize, cols: usize) -> Vec<Pattern> {
+        (0..rows).map(|i| (0..cols).map(|j| (i, j)).collect()).collect()
+    }
+}
+
+impl WinRule for Columns {
+    fn patterns(&self, rows: usize, cols: usize) -> Vec<Pattern> {
+        (0..cols).map(|j| (0..rows).map(|i| (i, j)).collect()).collect()
+    }
+}
+
+impl WinRule for Diagonals {
+    fn patterns(&self, rows: usize, cols: usize) -> Vec<Pattern> {
+        if rows != cols || rows == 0 {
+            return Vec::new();
+        }
+        vec![
+            (0..rows).map(|i| (i, i)).collect(),
+            (0..rows).map(|i| (i, cols - 1 - i)).collect(),
+        ]
+    }
+}
+
+impl WinRule for FourCorners {
+    fn patterns(&self, rows: usize, cols: usize) -> Vec<Pattern> {
+        if rows == 0 || cols == 0 {
+            return Vec::new();
+        }
+        let mut corners = vec![(0, 0), (0, cols - 1), (rows - 1, 0), (rows - 1, cols - 1)];
+        // Thin boards share corners
+        corners.sort_unstable();
+        corners.dedup();
+        vec![corners]
+    }
+}
+
+impl WinRule for Blackout {
+    fn patterns(&self, rows: usize, cols: usize) -> Vec<Pattern> {
+        vec![(0..rows).flat_map(|i| (0..cols).map(move |j| (i, j))).collect()]
+    }
+}
+
+impl Custom {
+    pub fn new(patterns: Vec<Pattern>) -> Custom {
+        Custom { patterns }
+    }
+
+    // Single pattern drawn as rows of 'x' (must be marked) and '.' (free), separated by '/' or new lines
+    pub fn from_grid(grid: &str) -> Result<Custom, String> {
+        let mut pattern = Pattern::new();
+        for (i, row) in grid.split(['/', '\n']).map(str::trim).filter(|row| !row.is_empty()).enumerate() {
+            for (j, c) in row.chars().enumerate() {
+                match c {
+                    'x' | 'X' => pattern.push((i, j)),
+                    '.' => (),
+                    _ => return Err(format!("Invalid character '{}' in pattern", c)),
+                }
+            }
+        }
+        if pattern.is_empty() {
+            return Err(String::from("Empty pattern"));
+        }
+        Ok(Custom::new(vec![pattern]))
+    }
+}
+
+impl WinRule for Custom {
+    fn patterns(&self, rows: usize, cols: usize) -> Vec<Pattern> {
+        // Patterns not fitting the board can never win
+        self.patterns.iter()
+            .filter(|pattern| pattern.iter().all(|&(i, j)| i < rows && j < cols))
+            .cloned()
+            .collect()
+    }
+}
+
+impl AnyOf {
+    pub fn new(rules: Vec<Box<dyn WinRule>>) -> AnyOf {
+        AnyOf { rules }
+    }
+
+    // Full rows and columns, as in the puzzle
+    pub fn standard() -> AnyOf {
+        AnyOf::new(vec![Box::new(Rows), Box::new(Columns)])
+    }
+}
+
+impl WinRule for AnyOf {
+    fn patterns(&self, rows: usize, cols: usize) -> Vec<Pattern> {
+        self.rules.iter().flat_map(|rule| rule.patterns(rows, cols)).collect()
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Board {
+        Board::from_rows(&[vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]])
+    }
+
+    fn mark_all(board: &mut Board, numbers: &[u8], rule: &dyn WinRule) {
+        for &number in numbers {
+            board.mark_if_present(number, rule);
+        }
+    }
+
+    #[test]
+    fn diagonals() {
+        let mut board = example();
+        mark_all(&mut board, &[3, 5], &Diagonals);
+        assert!(!board.wins());
+        mark_all(&mut board, &[7], &Diagonals);
+        assert_eq!(board.score(), Some(7 * (1 + 2 + 4 + 6 + 8 + 9)));
+        assert!(Diagonals.patterns(2, 3).is_empty());
+    }
+
+    #[test]
+    fn corners_and_blackout() {
+        let mut board = example();
+        mark_all(&mut board, &[1, 3, 7, 9], &FourCorners);
+        assert_eq!(board.winning_number(), Some(9));
+        let mut board = example();
+        mark_all(&mut board, &[1, 2, 3, 4, 5, 6, 7, 8], &Blackout);
+        assert!(!board.wins());
+        mark_all(&mut board, &[9], &Blackout);
+        assert_eq!(board.score(), Some(0));
+    }
+
+    #[test]
+    fn custom_pattern() {
+        let plus = Custom::from_grid(".x./xxx/.x.").unwrap();
+        assert_eq!(plus.patterns(3, 3), vec![vec![(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)]]);
+        assert!(plus.patterns(2, 3).is_empty());
+        let mut board = example();
+        mark_all(&mut board, &[2, 4, 5, 6], &plus);
+        assert!(!board.wins());
+        mark_all(&mut board, &[8], &plus);
+        assert_eq!(board.winning_number(), Some(8));
+        assert!(Custom::from_grid("x?x").is_err());
+    }
+
+    #[test]
+    fn standard_rule() {
+        let mut board = example();
+        let rule = AnyOf::standard();
+        mark_all(&mut board, &[1, 5, 9], &rule);
+        assert!(!board.wins());
+        mark_all(&mut board, &[2, 8], &rule);
+        assert_eq!(board.winning_number(), Some(8));
+    }
+}
@@ -0,0 +1,327 @@
+use crate::bingo::Board;
+use crate::rules::WinRule;
+
+// Set of bingo numbers, one bit each
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct NumberSet([u64; 4]);
+
+impl NumberSet {
+    fn new() -> NumberSet {
+        NumberSet([0; 4])
+    }
+
+    fn from(numbers: impl IntoIterator<Item = u8>) -> NumberSet {
+        let mut set = NumberSet::new();
+        for number in numbers {
+            set.insert(number);
+        }
+        set
+    }
+
+    fn insert(&mut self, number: u8) {
+        self.0[number as usize / 64] |= 1 << (number % 64);
+    }
+
+    fn remove(&mut self, number: u8) {
+        self.0[number as usize / 64] &= !(1 << (number % 64));
+    }
+
+    fn contains(&self, number: u8) -> bool {
+        self.0[number as usize / 64] & (1 << (number % 64)) != 0
+    }
+
+    fn union(&self, other: &NumberSet) -> NumberSet {
+        NumberSet([0, 1, 2, 3].map(|i| self.0[i] | other.0[i]))
+    }
+
+    fn difference(&self, other: &NumberSet) -> NumberSet {
+        NumberSet([0, 1, 2, 3].map(|i| self.0[i] & !other.0[i]))
+    }
+
+    fn is_subset(&self, other: &NumberSet) -> bool {
+        (0..4).all(|i| self.0[i] & !other.0[i] == 0)
+    }
+
+    fn len(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    fn numbers(&self) -> Vec<u8> {
+        (0..=u8::MAX).filter(|&number| self.contains(number)).collect()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Goal {
+    // The board wins before any other one
+    First,
+    // Every other board wins before it does
+    Last,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plan {
+    pub draws: Vec<u8>,
+    pub score: u64,
+}
+
+// Best plan found, and whether the search was complete. An incomplete search may have missed shorter sequences, or
+// any sequence at all.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outcome {
+    pub plan: Option<Plan>,
+    pub exhaustive: bool,
+}
+
+// Making a board win last means covering every other board, which takes exponential time in the number of boards,
+// so the search gives up after this many steps
+const MAX_STEPS: usize = 10_000;
+
+struct Search<'a> {
+    // Winning patterns of every board, as the numbers still to be drawn
+    patterns: Vec<Vec<NumberSet>>,
+    target: usize,
+    board: &'a Board,
+    bound: usize,
+    best: Option<(NumberSet, u8)>,
+    steps: usize,
+}
+
+impl Search<'_> {
+    fn wins(&self, board: usize, drawn: &NumberSet) -> bool {
+        self.patterns[board].iter().any(|pattern| pattern.is_subset(drawn))
+    }
+
+    fn score(&self, drawn: &NumberSet, last: u8) -> u64 {
+        let unmarked: u64 = self.board.cells().iter()
+            .filter(|cell| !cell.marked && !drawn.contains(cell.number))
+            .map(|cell| cell.number as u64)
+            .sum();
+        unmarked * last as u64
+    }
+
+    // Shorter sequences first, then higher scores
+    fn consider(&mut self, before: NumberSet, last: u8) {
+        let length = before.len() + 1;
+        if length > self.bound {
+            return;
+        }
+        let mut drawn = before;
+        drawn.insert(last);
+        let better = match self.best {
+            None => true,
+            Some((best_before, best_last)) => {
+                let best_length = best_before.len() + 1;
+                let mut best_drawn = best_before;
+                best_drawn.insert(best_last);
+                length < best_length
+                    || (length == best_length && self.score(&drawn, last) > self.score(&best_drawn, best_last))
+            }
+        };
+        if better {
+            self.best = Some((before, last));
+        }
+    }
+
+    // Completes the target board after `drawn`, without letting it win any earlier
+    fn finish(&mut self, drawn: &NumberSet) {
+        for p in 0..self.patterns[self.target].len() {
+            let missing = self.patterns[self.target][p].difference(drawn);
+            for last in missing.numbers() {
+                let mut before = drawn.union(&missing);
+                before.remove(last);
+                if !self.wins(self.target, &before) {
+                    self.consider(before, last);
+                }
+            }
+        }
+    }
+
+    fn best_length(&self) -> usize {
+        self.best.map_or(self.bound, |(before, _)| before.len() + 1)
+    }
+
+    // Fewest numbers to draw after `drawn` before the board wins, None when it cannot
+    fn missing(&self, board: usize, drawn: &NumberSet) -> Option<usize> {
+        self.patterns[board].iter().map(|pattern| pattern.difference(drawn).len()).min()
+    }
+
+    // Numbers still needed by the boards from `board` onwards: each of them wins in its own pattern, and the target
+    // board needs at least one more number. None when some board cannot win.
+    fn lower_bound(&self, board: usize, drawn: &NumberSet) -> Option<usize> {
+        let mut needed = 0;
+        for b in (board..self.patterns.len()).filter(|&b| b != self.target) {
+            needed = needed.max(self.missing(b, drawn)?);
+        }
+        Some(drawn.len() + needed + 1)
+    }
+
+    // Makes every other board from `board` onwards win within `drawn`, keeping the target board from winning
+    fn cover(&mut self, board: usize, drawn: NumberSet) {
+        if self.steps == MAX_STEPS {
+            return;
+        }
+        self.steps += 1;
+        match self.lower_bound(board, &drawn) {
+            Some(length) if length <= self.best_length() => (),
+            _ => return,
+        }
+        if board == self.patterns.len() {
+            self.finish(&drawn);
+            return;
+        }
+        if board == self.target || self.wins(board, &drawn) {
+            self.cover(board + 1, drawn);
+            return;
+        }
+        let mut options: Vec<NumberSet> = self.patterns[board].iter().map(|pattern| drawn.union(pattern)).collect();
+        options.sort_by_key(|option| option.len());
+        for option in options {
+            if !self.wins(self.target, &option) {
+                self.cover(board + 1, option);
+            }
+        }
+    }
+}
+
+// Looks for the shortest draw sequence, taken from `pool` and at most `bound` long, making board `target` win first
+// or last. Among the shortest sequences the one with the highest score is returned. Making a board win first is
+// always solved exactly, making it win last only when there are few boards: otherwise the outcome is the best
+// sequence found before giving up, boards taking the cheapest patterns first.
+pub fn fastest_win(boards: &[Board], rule: &dyn WinRule, target: usize, goal: Goal, pool: &[u8], bound: usize)
+    -> Outcome
+{
+    let pool = NumberSet::from(pool.iter().copied());
+    let patterns = boards.iter().map(|board| {
+        rule.patterns(board.rows(), board.cols()).iter()
+            .map(|pattern| {
+                let cells = pattern.iter().map(|&(i, j)| board.get_cell(i, j));
+                NumberSet::from(cells.filter(|cell| !cell.marked).map(|cell| cell.number))
+            })
+            .filter(|pattern| pattern.is_subset(&pool))
+            .collect()
+    }).collect();
+    let mut search = Search { patterns, target, board: &boards[target], bound, best: None, steps: 0 };
+    match goal {
+        Goal::First => {
+            // Drawing exactly one pattern of the target board is the quickest way, as long as no other board wins too
+            for p in 0..search.patterns[target].len() {
+                let drawn = search.patterns[target][p];
+                let others_win = (0..boards.len()).any(|b| b != target && search.wins(b, &drawn));
+                if others_win || drawn.len() == 0 {
+                    continue;
+                }
+                for last in drawn.numbers() {
+                    let mut before = drawn;
+                    before.remove(last);
+                    if !search.wins(target, &before) {
+                        search.consider(before, last);
+                    }
+                }
+            }
+        }
+        Goal::Last => search.cover(0, NumberSet::new()),
+    }
+    let plan = search.best.map(|(before, last)| {
+        let mut drawn = before;
+        drawn.insert(last);
+        let mut draws = before.numbers();
+        draws.push(last);
+        Plan { draws, score: search.score(&drawn, last) }
+    });
+    Outcome { plan, exhaustive: search.steps < MAX_STEPS }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Game;
+    use crate::rules::AnyOf;
+
+    fn boards() -> Vec<Board> {
+        vec![
+            Board::from_rows(&[vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]),
+            Board::from_rows(&[vec![1, 4, 10], vec![2, 5, 11], vec![12, 13, 14]]),
+            Board::from_rows(&[vec![3, 6, 9], vec![15, 16, 17], vec![18, 19, 20]]),
+        ]
+    }
+
+    // Checks the plan by actually playing it
+    fn check(boards: &[Board], target: usize, goal: Goal, plan: &Plan) {
+        let mut game = Game::new(boards.to_vec(), &AnyOf::standard());
+        let standings = game.play(&plan.draws);
+        let win = match goal {
+            Goal::First => standings.first(),
+            Goal::Last => standings.last(),
+        }.expect("Nobody won");
+        assert_eq!(win.board, target);
+        assert_eq!(win.draw, plan.draws.len() - 1);
+        assert_eq!(win.score, plan.score);
+        match goal {
+            Goal::First => assert_eq!(standings.wins.len(), 1),
+            Goal::Last => assert_eq!(standings.wins.iter().filter(|w| w.draw == win.draw).count(), 1),
+        }
+    }
+
+    #[test]
+    fn win_first() {
+        let boards = boards();
+        let pool: Vec<u8> = (1..=20).collect();
+        for target in 0..boards.len() {
+            let plan = fastest_win(&boards, &AnyOf::standard(), target, Goal::First, &pool, 10).plan.unwrap();
+            assert_eq!(plan.draws.len(), 3);
+            check(&boards, target, Goal::First, &plan);
+        }
+        // Column 3-6-9 would score best but is also a row of the last board
+        let plan = fastest_win(&boards, &AnyOf::standard(), 0, Goal::First, &pool, 10).plan.unwrap();
+        assert_eq!(plan, Plan { draws: vec![2, 5, 8], score: 8 * (1 + 3 + 4 + 6 + 7 + 9) });
+        assert_eq!(fastest_win(&boards, &AnyOf::standard(), 0, Goal::First, &pool, 2).plan, None);
+    }
+
+    #[test]
+    fn win_last() {
+        let boards = boards();
+        let pool: Vec<u8> = (1..=20).collect();
+        for target in 0..boards.len() {
+            let plan = fastest_win(&boards, &AnyOf::standard(), target, Goal::Last, &pool, 20).plan.unwrap();
+            check(&boards, target, Goal::Last, &plan);
+        }
+        // The other boards share no line, so they take 3 + 3 draws, and the first board still needs one more
+        let plan = fastest_win(&boards, &AnyOf::standard(), 0, Goal::Last, &pool, 20).plan.unwrap();
+        assert_eq!(plan.draws.len(), 7);
+        let outcome = fastest_win(&boards, &AnyOf::standard(), 0, Goal::Last, &pool, plan.draws.len() - 1);
+        assert_eq!(outcome, Outcome { plan: None, exhaustive: true });
+    }
+
+    #[test]
+    fn many_boards() {
+        // As many boards as in the puzzle input, shuffled by a linear congruential generator
+        let mut state = 42u64;
+        let boards: Vec<Board> = (0..100).map(|_| {
+            let mut numbers: Vec<u8> = (0..100).collect();
+            for i in (1..numbers.len()).rev() {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                numbers.swap(i, (state >> 33) as usize % (i + 1));
+            }
+            Board::from_rows(&numbers.chunks(5).take(5).map(|row| row.to_vec()).collect::<Vec<_>>())
+        }).collect();
+        let pool: Vec<u8> = (0..100).collect();
+        let outcome = fastest_win(&boards, &AnyOf::standard(), 7, Goal::Last, &pool, 100);
+        assert!(!outcome.exhaustive);
+        check(&boards, 7, Goal::Last, &outcome.plan.unwrap());
+        let outcome = fastest_win(&boards, &AnyOf::standard(), 7, Goal::First, &pool, 100);
+        assert!(outcome.exhaustive);
+        assert_eq!(outcome.plan.unwrap().draws.len(), 5);
+    }
+
+    #[test]
+    fn pool_limits_draws() {
+        // Without 5 the middle row and column are out of reach
+        let boards = vec![Board::from_rows(&[vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]])];
+        let pool = [1, 2, 3, 4, 6, 7, 8, 9];
+        let plan = fastest_win(&boards, &AnyOf::standard(), 0, Goal::First, &pool, 10).plan.unwrap();
+        assert!(!plan.draws.contains(&5));
+        assert_eq!(fastest_win(&boards, &AnyOf::standard(), 0, Goal::First, &[1, 3, 5, 7, 9], 10).plan, None);
+    }
+}
@@ -1,15 +1,20 @@
 use std::error::Error;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, BufWriter};
 
 pub mod oceanfloor {
+    pub mod heatmap;
+    pub mod intersection;
     pub mod map;
     pub mod point;
     pub mod rect;
     pub mod segment;
 }
-use oceanfloor::map::Map;
-use oceanfloor::segment::Segment;
+use oceanfloor::heatmap::Heatmap;
+use oceanfloor::intersection::Coverage;
+use oceanfloor::map::{self, Map};
+use oceanfloor::rect::Rect;
+use oceanfloor::segment::{Raster, Segment};
 
 fn read_segments<F>(fname: &str, filter: F) -> Result<Vec<Segment>, Box<dyn Error>>
 where
@@ -30,8 +35,8 @@ where
 
 fn part1() {
     println!("{:-<80}", "Part 1 ");
-    let m = Map::from(
-        &read_segments(&"data.txt", |&segment| {
+    let m = Coverage::new(
+        &read_segments("data.txt", |&segment| {
             segment.is_horizontal() || segment.is_vertical()
         })
         .expect("Could not read segments"),
@@ -44,9 +49,9 @@ fn part1() {
 }
 
 fn part2() {
-    println!("{:-<80}", "Part 1 ");
-    let m = Map::from(
-        &read_segments(&"data.txt", |&segment| {
+    println!("{:-<80}", "Part 2 ");
+    let m = Coverage::new(
+        &read_segments("data.txt", |&segment| {
             segment.is_horizontal() || segment.is_vertical() || segment.is_diagonal()
         })
         .expect("Could not read segments"),
@@ -57,7 +62,67 @@ fn part2() {
     );
 }
 
+// Larger floors would make images too big to look at
+const MAX_IMAGE_PIXELS: u64 = 4096 * 4096;
+
+fn export_heatmap(map: &dyn Map, segments: &[Segment], raster: Raster, overlay: bool) {
+    let region = segments
+        .iter()
+        .map(Segment::bbox)
+        .reduce(|r1, r2| Rect::union(&r1, &r2))
+        .expect("No segments");
+    if (region.width() + 1).saturating_mul(region.height() + 1) > MAX_IMAGE_PIXELS {
+        println!("Floor too large for a heatmap");
+        return;
+    }
+    let mut heatmap = Heatmap::new(map, &region);
+    if overlay {
+        heatmap.overlay(segments, raster);
+    }
+    let pgm = File::create("heatmap.pgm").expect("Could not create PGM file");
+    heatmap
+        .write_pgm(&mut BufWriter::new(pgm))
+        .expect("Could not write PGM file");
+    let ppm = File::create("heatmap.ppm").expect("Could not create PPM file");
+    heatmap
+        .write_ppm(&mut BufWriter::new(ppm))
+        .expect("Could not write PPM file");
+    println!("Heatmap written, up to {} overlapping lines", heatmap.max());
+}
+
+// Every segment whatever its slope, rasterised as given on the command line
+fn any_slope(raster: Raster, heatmap: bool, overlay: bool) {
+    println!("{:-<80}", "Any slope ");
+    let segments = read_segments("data.txt", |_| true).expect("Could not read segments");
+    let m = map::from_segments(&segments, raster);
+    println!(
+        "Points where at least two lines overlap ({:?}): {}",
+        raster,
+        m.count_overlaps(2)
+    );
+    if let Some((max, points)) = m.max_overlap() {
+        println!("Up to {} lines overlap, at {} points", max, points.len());
+        for segment in map::segments_through(&segments, &points[0], raster) {
+            println!("  {} goes through {}", segment, points[0]);
+        }
+    }
+    if heatmap {
+        export_heatmap(m.as_ref(), &segments, raster, overlay);
+    }
+}
+
+// Command line flags: --bresenham to draw segments as pixels, --heatmap to write heatmap.pgm and heatmap.ppm, and
+// --overlay to outline the segments on the heatmap
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let raster = if args.iter().any(|arg| arg == "--bresenham") {
+        Raster::Bresenham
+    } else {
+        Raster::Lattice
+    };
+    let heatmap = args.iter().any(|arg| arg == "--heatmap");
+    let overlay = args.iter().any(|arg| arg == "--overlay");
     part1();
     part2();
+    any_slope(raster, heatmap, overlay);
 }
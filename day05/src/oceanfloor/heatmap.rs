@@ -0,0 +1,177 @@
+use std::io::{Result, Write};
+
+use super::map::Map;
+use super::point::Point;
+use super::rect::Rect;
+use super::segment::{Raster, Segment};
+
+// Colour scale from a single segment to the most overlapped points
+const SCALE: [[u8; 3]; 5] = [
+    [0, 0, 255],
+    [0, 255, 255],
+    [0, 255, 0],
+    [255, 255, 0],
+    [255, 0, 0],
+];
+const EMPTY: [u8; 3] = [0, 0, 0];
+const OVERLAY: [u8; 3] = [255, 255, 255];
+
+// Overlap counts of a region of the map, ready to be written as an image. Rows go down with y, as in the puzzle.
+pub struct Heatmap {
+    region: Rect,
+    width: usize,
+    height: usize,
+    counts: Vec<u64>,
+    overlay: Vec<bool>,
+}
+
+impl Heatmap {
+    // Both corners of the region are included
+    pub fn new(map: &dyn Map, region: &Rect) -> Heatmap {
+        let width = region.width() as usize + 1;
+        let height = region.height() as usize + 1;
+        let mut counts = Vec::with_capacity(width * height);
+        for y in region.a.y..=region.b.y {
+            for x in region.a.x..=region.b.x {
+                counts.push(map.get(&Point::new(x, y)));
+            }
+        }
+        Heatmap {
+            region: *region,
+            width,
+            height,
+            counts,
+            overlay: vec![false; width * height],
+        }
+    }
+
+    // Outlines the segments on top of the counts, clipped to the region, with the points counted in the map
+    pub fn overlay(&mut self, segments: &[Segment], raster: Raster) {
+        for segment in segments {
+            for point in segment.points(raster) {
+                if self.region.contains(&point) {
+                    let local = point - self.region.a;
+                    self.overlay[local.y as usize * self.width + local.x as usize] = true;
+                }
+            }
+        }
+    }
+
+    pub fn max(&self) -> u64 {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+
+    // Binary greyscale image, brighter where more segments overlap
+    pub fn write_pgm<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let max = self.max().max(1);
+        write!(writer, "P5\n{} {}\n255\n", self.width, self.height)?;
+        let pixels: Vec<u8> = self
+            .counts
+            .iter()
+            .map(|&count| (count * 255 / max) as u8)
+            .collect();
+        writer.write_all(&pixels)
+    }
+
+    // Binary colour image, with the overlay in white
+    pub fn write_ppm<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let max = self.max();
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        let mut pixels = Vec::with_capacity(3 * self.counts.len());
+        for (&count, &overlay) in self.counts.iter().zip(&self.overlay) {
+            let colour = if overlay { OVERLAY } else { colour(count, max) };
+            pixels.extend_from_slice(&colour);
+        }
+        writer.write_all(&pixels)
+    }
+}
+
+// Interpolates the colour scale, from the first colour for 1 to the last one for `max`
+fn colour(count: u64, max: u64) -> [u8; 3] {
+    if count == 0 {
+        return EMPTY;
+    }
+    if max <= 1 {
+        return SCALE[0];
+    }
+    let position = (count - 1) as f64 / (max - 1) as f64 * (SCALE.len() - 1) as f64;
+    let index = (position.floor() as usize).min(SCALE.len() - 2);
+    let t = position - index as f64;
+    let (from, to) = (SCALE[index], SCALE[index + 1]);
+    [0, 1, 2].map(|c| (from[c] as f64 + t * (to[c] as f64 - from[c] as f64)).round() as u8)
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oceanfloor::map::SparseMap;
+
+    fn heatmap(segments: &[Segment]) -> Heatmap {
+        Heatmap::new(
+            &SparseMap::from(segments),
+            &Rect::new(&Point::new(0, 0), &Point::new(3, 2)),
+        )
+    }
+
+    #[test]
+    fn colour_scale() {
+        assert_eq!(colour(0, 5), EMPTY);
+        assert_eq!(colour(1, 1), SCALE[0]);
+        assert_eq!(colour(1, 5), SCALE[0]);
+        assert_eq!(colour(3, 5), SCALE[2]);
+        assert_eq!(colour(5, 5), SCALE[4]);
+        assert_eq!(colour(2, 3), SCALE[2]);
+    }
+
+    #[test]
+    fn images() {
+        let segments: Vec<Segment> = ["0,0 -> 3,0", "1,0 -> 1,2"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let mut heatmap = heatmap(&segments);
+        assert_eq!(heatmap.max(), 2);
+        let mut pgm = Vec::new();
+        heatmap.write_pgm(&mut pgm).unwrap();
+        let header = b"P5\n4 3\n255\n";
+        assert_eq!(&pgm[..header.len()], header);
+        assert_eq!(
+            &pgm[header.len()..],
+            &[127, 255, 127, 127, 0, 127, 0, 0, 0, 127, 0, 0]
+        );
+        let mut ppm = Vec::new();
+        heatmap.write_ppm(&mut ppm).unwrap();
+        let header = b"P6\n4 3\n255\n";
+        assert_eq!(ppm.len(), header.len() + 3 * 12);
+        assert_eq!(&ppm[header.len() + 3..header.len() + 6], &SCALE[4]);
+        assert_eq!(&ppm[header.len() + 12..header.len() + 15], &EMPTY);
+        // The overlay hides the counts
+        heatmap.overlay(&segments[1..], Raster::Lattice);
+        let mut ppm = Vec::new();
+        heatmap.write_ppm(&mut ppm).unwrap();
+        assert_eq!(&ppm[header.len() + 3..header.len() + 6], &OVERLAY);
+        assert_eq!(&ppm[header.len()..header.len() + 3], &SCALE[0]);
+    }
+
+    #[test]
+    fn overlay_follows_raster() {
+        let segments: Vec<Segment> = vec!["0,0 -> 3,2".parse().unwrap()];
+        let mut lattice = heatmap(&segments);
+        lattice.overlay(&segments, Raster::Lattice);
+        assert_eq!(
+            lattice.overlay.iter().filter(|&&outlined| outlined).count(),
+            2
+        );
+        let mut bresenham = heatmap(&segments);
+        bresenham.overlay(&segments, Raster::Bresenham);
+        assert_eq!(
+            bresenham
+                .overlay
+                .iter()
+                .filter(|&&outlined| outlined)
+                .count(),
+            4
+        );
+    }
+}
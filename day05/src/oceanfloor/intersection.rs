@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use super::point::Point;
+use super::segment::Segment;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Direction {
+    Horizontal,
+    Vertical,
+    // Going up-right, x - y is constant
+    Diagonal,
+    // Going down-right, x + y is constant
+    AntiDiagonal,
+}
+
+impl Direction {
+    // Coefficients (u, v) of the line equation u * x + v * y = key
+    fn equation(&self) -> (i64, i64) {
+        match self {
+            Direction::Horizontal => (0, 1),
+            Direction::Vertical => (1, 0),
+            Direction::Diagonal => (1, -1),
+            Direction::AntiDiagonal => (1, 1),
+        }
+    }
+
+    // Position of a point along the line
+    fn param(&self, point: &Point) -> i64 {
+        match self {
+            Direction::Vertical => point.y,
+            _ => point.x,
+        }
+    }
+}
+
+// Maximal piece of a line covered by the same number of segments
+#[derive(Copy, Clone, Debug)]
+struct Run {
+    direction: Direction,
+    key: i64,
+    // Inclusive range of positions along the line
+    start: i64,
+    end: i64,
+    coverage: u64,
+}
+
+impl Run {
+    fn len(&self) -> u64 {
+        (self.end - self.start + 1) as u64
+    }
+
+    fn contains(&self, point: &Point) -> bool {
+        let param = self.direction.param(point);
+        self.start <= param && param <= self.end
+    }
+
+    // Lattice point where the lines of both runs cross, if they are not parallel
+    fn crossing(&self, other: &Run) -> Option<Point> {
+        let (u1, v1) = self.direction.equation();
+        let (u2, v2) = other.direction.equation();
+        let det = u1 * v2 - u2 * v1;
+        if det == 0 {
+            return None;
+        }
+        let x = self.key * v2 - other.key * v1;
+        let y = u1 * other.key - u2 * self.key;
+        if x % det != 0 || y % det != 0 {
+            return None;
+        }
+        let point = Point::new(x / det, y / det);
+        if self.contains(&point) && other.contains(&point) {
+            Some(point)
+        } else {
+            None
+        }
+    }
+}
+
+fn line_of(segment: &Segment) -> (Direction, i64) {
+    let (a, b) = (segment.a, segment.b);
+    let direction = if segment.is_horizontal() {
+        Direction::Horizontal
+    } else if segment.is_vertical() {
+        Direction::Vertical
+    } else if (b.x - a.x) == (b.y - a.y) {
+        Direction::Diagonal
+    } else {
+        Direction::AntiDiagonal
+    };
+    let (u, v) = direction.equation();
+    (direction, u * a.x + v * a.y)
+}
+
+// Counts covered points without rasterising the segments. Collinear segments are merged into runs of constant
+// coverage, and the only points covered by runs of different directions are where they cross.
+pub struct Coverage {
+    runs: Vec<Run>,
+    // Runs going through each crossing point
+    crossings: HashMap<Point, Vec<usize>>,
+}
+
+impl Coverage {
+    pub fn new(segments: &[Segment]) -> Coverage {
+        let runs = Coverage::runs(segments);
+        let mut crossings: HashMap<Point, Vec<usize>> = HashMap::new();
+        for i in 0..runs.len() {
+            for j in i + 1..runs.len() {
+                if let Some(point) = runs[i].crossing(&runs[j]) {
+                    let through = crossings.entry(point).or_default();
+                    for run in [i, j] {
+                        if !through.contains(&run) {
+                            through.push(run);
+                        }
+                    }
+                }
+            }
+        }
+        Coverage { runs, crossings }
+    }
+
+    fn runs(segments: &[Segment]) -> Vec<Run> {
+        // Coverage changes along each line, as (position, delta)
+        let mut lines: HashMap<(Direction, i64), Vec<(i64, i64)>> = HashMap::new();
+        for segment in segments {
+            assert!(
+                segment.is_horizontal() || segment.is_vertical() || segment.is_diagonal(),
+                "Segment must be horizontal, vertical or diagonal"
+            );
+            let (direction, key) = line_of(segment);
+            let (a, b) = (direction.param(&segment.a), direction.param(&segment.b));
+            let events = lines.entry((direction, key)).or_default();
+            events.push((a.min(b), 1));
+            events.push((a.max(b) + 1, -1));
+        }
+        let mut lines: Vec<_> = lines.into_iter().collect();
+        lines.sort_unstable_by_key(|&(line, _)| line);
+        let mut runs = Vec::new();
+        for ((direction, key), mut events) in lines {
+            events.sort_unstable();
+            let mut coverage = 0;
+            for (i, &(position, delta)) in events.iter().enumerate() {
+                coverage += delta;
+                let next = events.get(i + 1).map(|&(next, _)| next);
+                if coverage > 0 && next != Some(position) {
+                    let end = next.expect("Unbalanced coverage") - 1;
+                    runs.push(Run {
+                        direction,
+                        key,
+                        start: position,
+                        end,
+                        coverage: coverage as u64,
+                    });
+                }
+            }
+        }
+        runs
+    }
+
+    // Number of points covered by at least `threshold` segments, which must be positive
+    pub fn count_overlaps(&self, threshold: u64) -> u64 {
+        assert!(threshold > 0, "Threshold must be positive");
+        let mut crossed = vec![0; self.runs.len()];
+        let mut count = 0;
+        for through in self.crossings.values() {
+            let coverage: u64 = through.iter().map(|&run| self.runs[run].coverage).sum();
+            if coverage >= threshold {
+                count += 1;
+            }
+            for &run in through {
+                crossed[run] += 1;
+            }
+        }
+        for (run, crossed) in self.runs.iter().zip(crossed) {
+            if run.coverage >= threshold {
+                count += run.len() - crossed;
+            }
+        }
+        count
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oceanfloor::map::{Map, SparseMap};
+
+    fn segments(lines: &[&str]) -> Vec<Segment> {
+        lines.iter().map(|line| line.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn example() {
+        let segments = segments(&[
+            "0,9 -> 5,9",
+            "8,0 -> 0,8",
+            "9,4 -> 3,4",
+            "2,2 -> 2,1",
+            "7,0 -> 7,4",
+            "6,4 -> 2,0",
+            "0,9 -> 2,9",
+            "3,4 -> 1,4",
+            "0,0 -> 8,8",
+            "5,5 -> 8,2",
+        ]);
+        let straight: Vec<Segment> = segments
+            .iter()
+            .filter(|segment| segment.is_horizontal() || segment.is_vertical())
+            .copied()
+            .collect();
+        assert_eq!(Coverage::new(&straight).count_overlaps(2), 5);
+        assert_eq!(Coverage::new(&segments).count_overlaps(2), 12);
+    }
+
+    #[test]
+    fn matches_rasterised_map() {
+        // Collinear overlaps, crossings of three or four directions, and diagonals crossing between lattice points
+        let segments = segments(&[
+            "0,5 -> 10,5",
+            "3,5 -> 7,5",
+            "5,5 -> 12,5",
+            "5,0 -> 5,10",
+            "5,2 -> 5,6",
+            "0,0 -> 10,10",
+            "0,10 -> 10,0",
+            "1,0 -> 0,1",
+            "0,0 -> 1,1",
+            "2,8 -> 8,2",
+            "4,4 -> 4,4",
+            "-3,-3 -> 3,3",
+        ]);
+        let coverage = Coverage::new(&segments);
+        let map = SparseMap::from(&segments);
+        for threshold in 1..8 {
+            let mut expected = 0;
+            for x in -5..15 {
+                for y in -5..15 {
+                    if map.get(&Point::new(x, y)) >= threshold {
+                        expected += 1;
+                    }
+                }
+            }
+            assert_eq!(
+                coverage.count_overlaps(threshold),
+                expected,
+                "Threshold {}",
+                threshold
+            );
+        }
+    }
+
+    #[test]
+    fn long_segments() {
+        let segments = segments(&[
+            "0,0 -> 1000000000,0",
+            "500000000,-5 -> 500000000,5",
+            "-10,-10 -> 2000000000,2000000000",
+            "0,0 -> 3000000000,0",
+        ]);
+        let coverage = Coverage::new(&segments);
+        // Only the shared part of the horizontal segments, crossed by the others at (0, 0) and (500000000, 0)
+        assert_eq!(coverage.count_overlaps(2), 1_000_000_001);
+        assert_eq!(coverage.count_overlaps(3), 2);
+        assert_eq!(coverage.count_overlaps(4), 0);
+    }
+}
@@ -1,53 +1,166 @@
 use crate::oceanfloor::point::Point;
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
 use super::rect::Rect;
-use super::segment::Segment;
+use super::segment::{Raster, Segment};
 
-pub struct Map {
+// Number of segments covering each point of the ocean floor
+pub trait Map {
+    fn get(&self, point: &Point) -> u64;
+
+    fn increment(&mut self, point: &Point);
+
+    // Points covered at least `threshold` times, and at least once whatever the threshold, so that both backends agree
+    fn count_overlaps(&self, threshold: u64) -> u64;
+
+    // Every point covered at least once, with its count, in no particular order
+    fn covered(&self) -> Vec<(Point, u64)>;
+
+    // Points covered at least `threshold` times, sorted
+    fn overlapping_points(&self, threshold: u64) -> Vec<Point> {
+        let mut points: Vec<Point> = self
+            .covered()
+            .into_iter()
+            .filter(|&(_, count)| count >= threshold)
+            .map(|(point, _)| point)
+            .collect();
+        points.sort_unstable();
+        points
+    }
+
+    // Points of the region, borders included, covered at least `threshold` times
+    fn count_overlaps_in(&self, region: &Rect, threshold: u64) -> u64 {
+        self.covered()
+            .iter()
+            .filter(|(point, count)| *count >= threshold && region.contains(point))
+            .count() as u64
+    }
+
+    // Highest count and the sorted points where it is reached, None if nothing is covered
+    fn max_overlap(&self) -> Option<(u64, Vec<Point>)> {
+        let covered = self.covered();
+        let max = covered.iter().map(|&(_, count)| count).max()?;
+        Some((max, self.overlapping_points(max)))
+    }
+
+    fn add_segment(&mut self, segment: &Segment) {
+        self.add_segment_with(segment, Raster::Lattice);
+    }
+
+    fn add_segment_with(&mut self, segment: &Segment, raster: Raster) {
+        for point in segment.points(raster) {
+            self.increment(&point);
+        }
+    }
+}
+
+// Above this ratio between the bounding box area and the number of points drawn, most cells of a dense map would
+// stay empty and a sparse one takes less memory
+const MAX_DENSE_RATIO: u64 = 4;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MapKind {
+    Dense,
+    Sparse,
+}
+
+impl MapKind {
+    pub fn choose(segments: &[Segment], raster: Raster) -> MapKind {
+        let boundaries = region_enclosing(segments);
+        let area = boundaries.width().saturating_mul(boundaries.height());
+        // Overlapping points are counted twice, which only makes the dense map more likely
+        let points: u64 = segments
+            .iter()
+            .map(|segment| segment.point_count(raster))
+            .sum();
+        if area <= points.saturating_mul(MAX_DENSE_RATIO) {
+            MapKind::Dense
+        } else {
+            MapKind::Sparse
+        }
+    }
+}
+
+// Builds the map best suited to the segments
+pub fn from_segments(segments: &[Segment], raster: Raster) -> Box<dyn Map> {
+    let mut map: Box<dyn Map> = match MapKind::choose(segments, raster) {
+        MapKind::Dense => Box::new(DenseMap::new(&region_enclosing(segments))),
+        MapKind::Sparse => Box::new(SparseMap::new()),
+    };
+    for segment in segments {
+        map.add_segment_with(segment, raster);
+    }
+    map
+}
+
+// Segments covering the point once rasterised
+pub fn segments_through<'a>(
+    segments: &'a [Segment],
+    point: &Point,
+    raster: Raster,
+) -> Vec<&'a Segment> {
+    segments
+        .iter()
+        .filter(|segment| match raster {
+            Raster::Lattice => segment.contains(point),
+            Raster::Bresenham => {
+                segment.bbox().contains(point) && segment.pixels().any(|pixel| pixel == *point)
+            }
+        })
+        .collect()
+}
+
+fn region_enclosing(segments: &[Segment]) -> Rect {
+    assert!(
+        !segments.is_empty(),
+        "Cannot create map from an empty Vec of Segments"
+    );
+    // Find boundaries
+    let mut boundaries = segments[0].bbox();
+    for segment in segments {
+        boundaries = Rect::union(&boundaries, &segment.bbox());
+    }
+    // Enlarge boundaries to enclose the extreme points
+    boundaries.b += Point::new(1, 1);
+    boundaries
+}
+
+// One counter for every point of the bounding box
+pub struct DenseMap {
     boundaries: Rect,
     data: Vec<u64>,
 }
 
-impl Map {
-    pub fn new(boundaries: &Rect) -> Map {
+impl DenseMap {
+    pub fn new(boundaries: &Rect) -> DenseMap {
         // Initialize data
         let data = vec![0; (boundaries.width() * boundaries.height()) as usize];
-        Map {
+        DenseMap {
             boundaries: *boundaries,
             data,
         }
     }
 
-    pub fn from(segments: &[Segment]) -> Map {
-        let mut map = Map::new(&Map::region_enclosing(segments));
+    pub fn from(segments: &[Segment]) -> DenseMap {
+        let mut map = DenseMap::new(&region_enclosing(segments));
         for segment in segments {
             map.add_segment(segment);
         }
         map
     }
 
-    fn region_enclosing(segments: &[Segment]) -> Rect {
-        assert!(
-            !segments.is_empty(),
-            "Cannot create map from an empty Vec of Segments"
-        );
-        // Find boundaries
-        let mut boundaries = segments[0].bbox();
-        for segment in segments {
-            boundaries = Rect::union(&boundaries, &segment.bbox());
-        }
-        // Enlarge boundaries to enclose the extreme points
-        boundaries.b += Point::new(1, 1);
-        boundaries
-    }
-
     pub fn boundaries(&self) -> &Rect {
         &self.boundaries
     }
 
     pub fn contains(&self, point: &Point) -> bool {
-        self.boundaries.contains(point)
+        // The far corner of the boundaries lies just outside the map
+        let local = *point - self.boundaries.a;
+        local.x >= 0
+            && local.y >= 0
+            && (local.x as u64) < self.boundaries.width()
+            && (local.y as u64) < self.boundaries.height()
     }
 
     fn data(&self, p: &Point) -> &u64 {
@@ -59,34 +172,51 @@ impl Map {
         let local = *p - self.boundaries.a;
         &mut self.data[(local.x + local.y * (self.boundaries.width() as i64)) as usize]
     }
+}
 
-    pub fn add_segment(&mut self, segment: &Segment) {
-        assert!(
-            segment.is_horizontal() || segment.is_vertical() || segment.is_diagonal(),
-            "Segment must be horizontal, vertical or diagonal"
-        );
-        for point in segment {
-            assert!(self.contains(&point), "Map does not contain the point");
-            *self.data_mut(&point) += 1;
+impl Map for DenseMap {
+    fn get(&self, point: &Point) -> u64 {
+        if self.contains(point) {
+            *self.data(point)
+        } else {
+            0
         }
     }
 
-    pub fn count_overlaps(&self, threshold: u64) -> u64 {
+    fn increment(&mut self, point: &Point) {
+        assert!(self.contains(point), "Map does not contain the point");
+        *self.data_mut(point) += 1;
+    }
+
+    fn count_overlaps(&self, threshold: u64) -> u64 {
         let mut count = 0;
         for &value in &self.data {
-            if value >= threshold {
+            if value > 0 && value >= threshold {
                 count += 1;
             }
         }
         count
     }
+
+    fn covered(&self) -> Vec<(Point, u64)> {
+        let width = self.boundaries.width() as i64;
+        self.data
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0)
+            .map(|(i, &count)| {
+                let i = i as i64;
+                (self.boundaries.a + Point::new(i % width, i / width), count)
+            })
+            .collect()
+    }
 }
 
-impl Display for Map {
+impl Display for DenseMap {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         for (i, &data) in self.data.iter().enumerate() {
             let i = i as u64;
-            if (i > 0) && (i % self.boundaries.width() == 0) {
+            if (i > 0) && i.is_multiple_of(self.boundaries.width()) {
                 writeln!(f)?;
             }
             if data == 0 {
@@ -98,3 +228,162 @@ impl Display for Map {
         Ok(())
     }
 }
+
+// Counters only for the points covered by some segment
+#[derive(Default)]
+pub struct SparseMap {
+    data: HashMap<Point, u64>,
+}
+
+impl SparseMap {
+    pub fn new() -> SparseMap {
+        SparseMap {
+            data: HashMap::new(),
+        }
+    }
+
+    pub fn from(segments: &[Segment]) -> SparseMap {
+        let mut map = SparseMap::new();
+        for segment in segments {
+            map.add_segment(segment);
+        }
+        map
+    }
+}
+
+impl Map for SparseMap {
+    fn get(&self, point: &Point) -> u64 {
+        self.data.get(point).copied().unwrap_or(0)
+    }
+
+    fn increment(&mut self, point: &Point) {
+        *self.data.entry(*point).or_insert(0) += 1;
+    }
+
+    fn count_overlaps(&self, threshold: u64) -> u64 {
+        self.data
+            .values()
+            .filter(|&&value| value > 0 && value >= threshold)
+            .count() as u64
+    }
+
+    fn covered(&self) -> Vec<(Point, u64)> {
+        self.data
+            .iter()
+            .map(|(&point, &count)| (point, count))
+            .collect()
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(lines: &[&str]) -> Vec<Segment> {
+        lines.iter().map(|line| line.parse().unwrap()).collect()
+    }
+
+    fn example() -> Vec<Segment> {
+        segments(&[
+            "0,9 -> 5,9",
+            "8,0 -> 0,8",
+            "9,4 -> 3,4",
+            "2,2 -> 2,1",
+            "7,0 -> 7,4",
+            "6,4 -> 2,0",
+            "0,9 -> 2,9",
+            "3,4 -> 1,4",
+            "0,0 -> 8,8",
+            "5,5 -> 8,2",
+        ])
+    }
+
+    #[test]
+    fn backends_agree() {
+        let segments = example();
+        let straight: Vec<Segment> = segments
+            .iter()
+            .filter(|segment| segment.is_horizontal() || segment.is_vertical())
+            .copied()
+            .collect();
+        assert_eq!(DenseMap::from(&straight).count_overlaps(2), 5);
+        assert_eq!(SparseMap::from(&straight).count_overlaps(2), 5);
+        let dense = DenseMap::from(&segments);
+        let sparse = SparseMap::from(&segments);
+        assert_eq!(dense.count_overlaps(2), 12);
+        assert_eq!(sparse.count_overlaps(2), 12);
+        // Uncovered points inside the dense grid are never counted
+        assert_eq!(dense.count_overlaps(0), sparse.count_overlaps(0));
+        assert_eq!(dense.count_overlaps(0), dense.count_overlaps(1));
+        for x in -1..11 {
+            for y in -1..11 {
+                let point = Point::new(x, y);
+                assert_eq!(dense.get(&point), sparse.get(&point), "{}", point);
+            }
+        }
+    }
+
+    #[test]
+    fn queries() {
+        let segments = example();
+        let maps: [Box<dyn Map>; 2] = [
+            Box::new(DenseMap::from(&segments)),
+            Box::new(SparseMap::from(&segments)),
+        ];
+        for map in maps {
+            assert_eq!(map.overlapping_points(2).len(), 12);
+            assert_eq!(
+                map.overlapping_points(3),
+                vec![Point::new(4, 4), Point::new(6, 4)]
+            );
+            assert_eq!(map.max_overlap(), Some((3, map.overlapping_points(3))));
+            let top_left = Rect::new(&Point::new(0, 0), &Point::new(4, 4));
+            assert_eq!(map.count_overlaps_in(&top_left, 2), 3);
+            assert_eq!(map.count_overlaps_in(&top_left, 1), 12);
+        }
+        assert_eq!(SparseMap::new().max_overlap(), None);
+    }
+
+    #[test]
+    fn segments_through_point() {
+        let segments = example();
+        let through = segments_through(&segments, &Point::new(4, 4), Raster::Lattice);
+        assert_eq!(through, vec![&segments[1], &segments[2], &segments[8]]);
+        assert!(segments_through(&segments, &Point::new(9, 9), Raster::Lattice).is_empty());
+        let slanted = ["0,0 -> 4,2".parse().unwrap(), "1,0 -> 2,3".parse().unwrap()];
+        assert!(segments_through(&slanted, &Point::new(1, 1), Raster::Lattice).is_empty());
+        assert_eq!(
+            segments_through(&slanted, &Point::new(1, 1), Raster::Bresenham).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn choice_of_backend() {
+        assert_eq!(MapKind::choose(&example(), Raster::Lattice), MapKind::Dense);
+        let far_apart = segments(&["0,0 -> 3,0", "1000000,1000000 -> 1000000,1000003"]);
+        assert_eq!(
+            MapKind::choose(&far_apart, Raster::Lattice),
+            MapKind::Sparse
+        );
+        assert_eq!(
+            from_segments(&far_apart, Raster::Lattice).count_overlaps(1),
+            8
+        );
+    }
+
+    #[test]
+    fn any_slope() {
+        // Only (2, 1) lies exactly on two segments, but the pixels of all three meet at (1, 1)
+        let segments = segments(&["0,0 -> 4,2", "0,1 -> 4,1", "1,0 -> 2,3"]);
+        let lattice = from_segments(&segments, Raster::Lattice);
+        assert_eq!(lattice.get(&Point::new(2, 1)), 2);
+        assert_eq!(lattice.get(&Point::new(1, 1)), 1);
+        assert_eq!(lattice.count_overlaps(2), 1);
+        let pixels = from_segments(&segments, Raster::Bresenham);
+        assert_eq!(pixels.get(&Point::new(2, 1)), 2);
+        assert_eq!(pixels.get(&Point::new(1, 1)), 3);
+        assert_eq!(pixels.count_overlaps(2), 2);
+    }
+}
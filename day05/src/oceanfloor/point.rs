@@ -1,9 +1,10 @@
+use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
 use std::num::ParseIntError;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 use std::str::FromStr;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Point {
     pub x: i64,
     pub y: i64,
@@ -13,6 +14,49 @@ impl Point {
     pub fn new(x: i64, y: i64) -> Point {
         Point { x, y }
     }
+
+    pub fn manhattan(&self, other: &Point) -> u64 {
+        (self.x - other.x).unsigned_abs() + (self.y - other.y).unsigned_abs()
+    }
+
+    // Number of king moves between the points
+    pub fn chebyshev(&self, other: &Point) -> u64 {
+        (self.x - other.x)
+            .unsigned_abs()
+            .max((self.y - other.y).unsigned_abs())
+    }
+
+    // Rotates by quarter turns around the center, counterclockwise with y pointing up (clockwise on screen)
+    pub fn rotate(&self, center: &Point, quarter_turns: i32) -> Point {
+        let d = *self - *center;
+        let d = match quarter_turns.rem_euclid(4) {
+            0 => d,
+            1 => Point::new(-d.y, d.x),
+            2 => Point::new(-d.x, -d.y),
+            _ => Point::new(d.y, -d.x),
+        };
+        *center + d
+    }
+
+    // Mirror image across the vertical line at x = axis
+    pub fn reflect_x(&self, axis: i64) -> Point {
+        Point::new(2 * axis - self.x, self.y)
+    }
+
+    // Mirror image across the horizontal line at y = axis
+    pub fn reflect_y(&self, axis: i64) -> Point {
+        Point::new(self.x, 2 * axis - self.y)
+    }
+
+    // Mirror image across the diagonal x = y
+    pub fn transpose(&self) -> Point {
+        Point::new(self.y, self.x)
+    }
+
+    // Cross product of the vectors from the origin
+    pub fn cross(&self, other: &Point) -> i64 {
+        self.x * other.y - self.y * other.x
+    }
 }
 
 impl Display for Point {
@@ -27,13 +71,54 @@ impl Debug for Point {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParsePointError {
+    Empty,
+    MissingComma,
+    // Anything after the y coordinate
+    ExtraField(String),
+    InvalidX(ParseIntError),
+    InvalidY(ParseIntError),
+}
+
+impl Display for ParsePointError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ParsePointError::Empty => write!(f, "empty point"),
+            ParsePointError::MissingComma => write!(f, "missing comma between coordinates"),
+            ParsePointError::ExtraField(extra) => write!(f, "unexpected \"{}\" after point", extra),
+            ParsePointError::InvalidX(e) => write!(f, "invalid x coordinate: {}", e),
+            ParsePointError::InvalidY(e) => write!(f, "invalid y coordinate: {}", e),
+        }
+    }
+}
+
+impl Error for ParsePointError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParsePointError::InvalidX(e) | ParsePointError::InvalidY(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// Parses "x,y", with optional spaces around both coordinates
 impl FromStr for Point {
-    type Err = ParseIntError;
+    type Err = ParsePointError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let numbers: Vec<&str> = s.split(',').collect();
-        let x = numbers[0].parse::<i64>()?;
-        let y = numbers[1].parse::<i64>()?;
+        if s.trim().is_empty() {
+            return Err(ParsePointError::Empty);
+        }
+        let (x, rest) = s.split_once(',').ok_or(ParsePointError::MissingComma)?;
+        if let Some((_, extra)) = rest.split_once(',') {
+            return Err(ParsePointError::ExtraField(format!(",{}", extra)));
+        }
+        let x = x.trim().parse::<i64>().map_err(ParsePointError::InvalidX)?;
+        let y = rest
+            .trim()
+            .parse::<i64>()
+            .map_err(ParsePointError::InvalidY)?;
         Ok(Point { x, y })
     }
 }
@@ -73,3 +158,89 @@ impl Sub for Point {
         }
     }
 }
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        let (a, b) = (Point::new(1, -2), Point::new(-3, 4));
+        assert_eq!(a.manhattan(&b), 10);
+        assert_eq!(a.chebyshev(&b), 6);
+        assert_eq!(b.manhattan(&a), 10);
+        assert_eq!(a.chebyshev(&a), 0);
+    }
+
+    #[test]
+    fn rotations() {
+        let origin = Point::new(0, 0);
+        let p = Point::new(2, 1);
+        assert_eq!(p.rotate(&origin, 1), Point::new(-1, 2));
+        assert_eq!(p.rotate(&origin, 2), Point::new(-2, -1));
+        assert_eq!(p.rotate(&origin, -1), Point::new(1, -2));
+        assert_eq!(p.rotate(&origin, 4), p);
+        assert_eq!(p.rotate(&Point::new(1, 1), 1), Point::new(1, 2));
+    }
+
+    #[test]
+    fn reflections() {
+        let p = Point::new(2, 5);
+        assert_eq!(p.reflect_x(0), Point::new(-2, 5));
+        assert_eq!(p.reflect_x(3), Point::new(4, 5));
+        assert_eq!(p.reflect_y(1), Point::new(2, -3));
+        assert_eq!(p.transpose(), Point::new(5, 2));
+        assert_eq!(p.reflect_y(7).reflect_y(7), p);
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("3,-4".parse(), Ok(Point::new(3, -4)));
+        assert_eq!("  3 ,\t4 ".parse(), Ok(Point::new(3, 4)));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("".parse::<Point>(), Err(ParsePointError::Empty));
+        assert_eq!("   ".parse::<Point>(), Err(ParsePointError::Empty));
+        assert_eq!("3 4".parse::<Point>(), Err(ParsePointError::MissingComma));
+        assert_eq!(
+            "1,2,3".parse::<Point>(),
+            Err(ParsePointError::ExtraField(String::from(",3")))
+        );
+        assert!(matches!(
+            "a,2".parse::<Point>(),
+            Err(ParsePointError::InvalidX(_))
+        ));
+        assert!(matches!(
+            ",2".parse::<Point>(),
+            Err(ParsePointError::InvalidX(_))
+        ));
+        assert!(matches!(
+            "1,".parse::<Point>(),
+            Err(ParsePointError::InvalidY(_))
+        ));
+        // Trailing garbage glued to the last coordinate
+        assert!(matches!(
+            "1,2x".parse::<Point>(),
+            Err(ParsePointError::InvalidY(_))
+        ));
+        assert!(matches!(
+            "1,99999999999999999999".parse::<Point>(),
+            Err(ParsePointError::InvalidY(_))
+        ));
+        let message = "1,2,3".parse::<Point>().unwrap_err().to_string();
+        assert_eq!(message, "unexpected \",3\" after point");
+    }
+
+    #[test]
+    fn ordering() {
+        let mut points = vec![Point::new(1, 2), Point::new(0, 5), Point::new(1, -1)];
+        points.sort();
+        assert_eq!(
+            points,
+            vec![Point::new(0, 5), Point::new(1, -1), Point::new(1, 2)]
+        );
+    }
+}
@@ -22,15 +22,83 @@ impl Rect {
         }
     }
 
+    // Common part of both rectangles, if they touch at all
+    pub fn intersection(r1: &Rect, r2: &Rect) -> Option<Rect> {
+        let a = Point::new(cmp::max(r1.a.x, r2.a.x), cmp::max(r1.a.y, r2.a.y));
+        let b = Point::new(cmp::min(r1.b.x, r2.b.x), cmp::min(r1.b.y, r2.b.y));
+        if a.x <= b.x && a.y <= b.y {
+            Some(Rect { a, b })
+        } else {
+            None
+        }
+    }
+
     pub fn width(&self) -> u64 {
-        (self.a.x - self.b.x).abs() as u64
+        (self.a.x - self.b.x).unsigned_abs()
     }
 
     pub fn height(&self) -> u64 {
-        (self.a.y - self.b.y).abs() as u64
+        (self.a.y - self.b.y).unsigned_abs()
+    }
+
+    // Number of points with integer coordinates, borders included like in `points` and `contains`
+    pub fn area(&self) -> u64 {
+        (self.width() + 1) * (self.height() + 1)
+    }
+
+    // Points with integer coordinates inside the rectangle, borders included, row by row
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let (a, b) = (self.a, self.b);
+        (a.y..=b.y).flat_map(move |y| (a.x..=b.x).map(move |x| Point::new(x, y)))
     }
 
     pub fn contains(&self, point: &Point) -> bool {
         (self.a.x <= point.x && point.x <= self.b.x) && (self.a.y <= point.y && point.y <= self.b.y)
     }
 }
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x1: i64, y1: i64, x2: i64, y2: i64) -> Rect {
+        Rect::new(&Point::new(x1, y1), &Point::new(x2, y2))
+    }
+
+    fn corners(r: Option<Rect>) -> Option<(Point, Point)> {
+        r.map(|r| (r.a, r.b))
+    }
+
+    #[test]
+    fn intersection() {
+        let r = rect(0, 0, 4, 3);
+        assert_eq!(
+            corners(Rect::intersection(&r, &rect(2, -1, 6, 2))),
+            Some((Point::new(2, 0), Point::new(4, 2)))
+        );
+        assert_eq!(
+            corners(Rect::intersection(&r, &rect(1, 1, 2, 2))),
+            Some((Point::new(1, 1), Point::new(2, 2)))
+        );
+        // Sharing an edge still intersects
+        assert_eq!(
+            corners(Rect::intersection(&r, &rect(4, 3, 5, 5))),
+            Some((Point::new(4, 3), Point::new(4, 3)))
+        );
+        assert!(Rect::intersection(&r, &rect(5, 0, 6, 3)).is_none());
+    }
+
+    #[test]
+    fn area_and_points() {
+        let r = rect(3, 2, 1, 1);
+        assert_eq!(r.area(), 6);
+        let points: Vec<Point> = r.points().collect();
+        assert_eq!(points.len() as u64, r.area());
+        assert_eq!(points[0], Point::new(1, 1));
+        assert_eq!(points[5], Point::new(3, 2));
+        assert!(points.iter().all(|p| r.contains(p)));
+        assert_eq!(rect(1, 1, 1, 1).points().count(), 1);
+        assert_eq!(rect(1, 1, 1, 1).area(), 1);
+    }
+}
@@ -1,16 +1,28 @@
+use std::cmp::{self, Ordering};
+use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
-use std::num::ParseIntError;
 use std::str::FromStr;
 
-use super::point::Point;
+use super::point::{ParsePointError, Point};
 use super::rect::Rect;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub struct Segment {
     pub a: Point,
     pub b: Point,
 }
 
+// Where two segments meet
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Intersection {
+    // Single point with integer coordinates
+    Lattice(Point),
+    // Single point between lattice points
+    Between(f64, f64),
+    // Collinear segments sharing more than one point
+    Overlap(Segment),
+}
+
 impl Segment {
     pub fn new(a: &Point, b: &Point) -> Segment {
         Segment { a: *a, b: *b }
@@ -31,6 +43,98 @@ impl Segment {
     pub fn is_diagonal(&self) -> bool {
         (self.a.x - self.b.x).abs() == (self.a.y - self.b.y).abs()
     }
+
+    // Number of points produced by rasterising the segment
+    pub fn point_count(&self, raster: Raster) -> u64 {
+        let d = self.b - self.a;
+        let (dx, dy) = (d.x.unsigned_abs(), d.y.unsigned_abs());
+        match raster {
+            Raster::Lattice => gcd(dx, dy) + 1,
+            Raster::Bresenham => dx.max(dy) + 1,
+        }
+    }
+
+    pub fn points(&self, raster: Raster) -> Box<dyn Iterator<Item = Point>> {
+        match raster {
+            Raster::Lattice => Box::new(self.into_iter()),
+            Raster::Bresenham => Box::new(self.pixels()),
+        }
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        (self.b - self.a).cross(&(*point - self.a)) == 0 && self.bbox().contains(point)
+    }
+
+    pub fn intersection(&self, other: &Segment) -> Option<Intersection> {
+        let d1 = self.b - self.a;
+        let d2 = other.b - other.a;
+        let offset = other.a - self.a;
+        let denominator = d1.cross(&d2) as i128;
+        if denominator == 0 {
+            return self.collinear_intersection(other);
+        }
+        // Positions along each segment, as fractions of the denominator
+        let (mut t, mut u) = (offset.cross(&d2) as i128, offset.cross(&d1) as i128);
+        let mut denominator = denominator;
+        if denominator < 0 {
+            (t, u, denominator) = (-t, -u, -denominator);
+        }
+        if t < 0 || t > denominator || u < 0 || u > denominator {
+            return None;
+        }
+        let (x, y) = (d1.x as i128 * t, d1.y as i128 * t);
+        if x % denominator == 0 && y % denominator == 0 {
+            let step = Point::new((x / denominator) as i64, (y / denominator) as i64);
+            Some(Intersection::Lattice(self.a + step))
+        } else {
+            let fraction = t as f64 / denominator as f64;
+            Some(Intersection::Between(
+                self.a.x as f64 + d1.x as f64 * fraction,
+                self.a.y as f64 + d1.y as f64 * fraction,
+            ))
+        }
+    }
+
+    // Intersection of parallel segments, only possible on the same line
+    fn collinear_intersection(&self, other: &Segment) -> Option<Intersection> {
+        let on_line = |segment: &Segment, point: &Point| {
+            let d = segment.b - segment.a;
+            d.cross(&(*point - segment.a)) == 0
+        };
+        let line = if self.a != self.b { self } else { other };
+        if !on_line(line, &self.a)
+            || !on_line(line, &self.b)
+            || !on_line(line, &other.a)
+            || !on_line(line, &other.b)
+        {
+            return None;
+        }
+        if self.a == self.b && other.a == other.b && self.a != other.a {
+            return None;
+        }
+        // Points of a line are ordered along it
+        let start = cmp::max(cmp::min(self.a, self.b), cmp::min(other.a, other.b));
+        let end = cmp::min(cmp::max(self.a, self.b), cmp::max(other.a, other.b));
+        match start.cmp(&end) {
+            Ordering::Less => Some(Intersection::Overlap(Segment::new(&start, &end))),
+            Ordering::Equal => Some(Intersection::Lattice(start)),
+            Ordering::Greater => None,
+        }
+    }
+
+    pub fn pixels(&self) -> PixelIterator {
+        let d = self.b - self.a;
+        PixelIterator {
+            current: self.a,
+            target: self.b,
+            dx: d.x.abs(),
+            dy: -d.y.abs(),
+            sx: d.x.signum(),
+            sy: d.y.signum(),
+            error: d.x.abs() - d.y.abs(),
+            finished: false,
+        }
+    }
 }
 
 impl Display for Segment {
@@ -45,56 +149,326 @@ impl Debug for Segment {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseSegmentError {
+    MissingArrow,
+    // Chained segments such as "a -> b -> c"
+    ExtraArrow,
+    InvalidStart(ParsePointError),
+    InvalidEnd(ParsePointError),
+}
+
+impl Display for ParseSegmentError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ParseSegmentError::MissingArrow => write!(f, "missing \"->\" between points"),
+            ParseSegmentError::ExtraArrow => write!(f, "more than one \"->\""),
+            ParseSegmentError::InvalidStart(e) => write!(f, "invalid start point: {}", e),
+            ParseSegmentError::InvalidEnd(e) => write!(f, "invalid end point: {}", e),
+        }
+    }
+}
+
+impl Error for ParseSegmentError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseSegmentError::InvalidStart(e) | ParseSegmentError::InvalidEnd(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// Parses "x1,y1 -> x2,y2", spaces around the arrow being optional
 impl FromStr for Segment {
-    type Err = ParseIntError;
+    type Err = ParseSegmentError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let points: Vec<&str> = s.split("->").collect();
-        let a = points[0].trim().parse::<Point>()?;
-        let b = points[1].trim().parse::<Point>()?;
+        let (a, b) = s.split_once("->").ok_or(ParseSegmentError::MissingArrow)?;
+        if b.contains("->") {
+            return Err(ParseSegmentError::ExtraArrow);
+        }
+        let a = a
+            .parse::<Point>()
+            .map_err(ParseSegmentError::InvalidStart)?;
+        let b = b.parse::<Point>().map_err(ParseSegmentError::InvalidEnd)?;
         Ok(Segment { a, b })
     }
 }
 
-pub struct SegmentIterator<'a> {
-    segment: &'a Segment,
+// How a segment is turned into points
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Raster {
+    // Only the points with integer coordinates lying exactly on the segment
+    Lattice,
+    // One point per step along the major axis, as drawn by Bresenham's algorithm
+    Bresenham,
+}
+
+// Lattice points of a segment, from a to b
+pub struct SegmentIterator {
     current: Point,
     step: Point,
+    remaining: u64,
 }
 
-impl<'a> IntoIterator for &'a Segment {
+impl IntoIterator for &Segment {
     type Item = Point;
-    type IntoIter = SegmentIterator<'a>;
+    type IntoIter = SegmentIterator;
 
     fn into_iter(self) -> Self::IntoIter {
-        let mut step = self.b - self.a;
-        for coord in [&mut step.x, &mut step.y] {
-            if *coord != 0 {
-                *coord /= coord.abs();
-            }
-        }
+        let d = self.b - self.a;
+        let steps = gcd(d.x.unsigned_abs(), d.y.unsigned_abs());
+        let step = if steps == 0 {
+            d
+        } else {
+            Point::new(d.x / steps as i64, d.y / steps as i64)
+        };
         SegmentIterator {
-            segment: self,
             current: self.a,
             step,
+            remaining: steps + 1,
+        }
+    }
+}
+
+impl Iterator for SegmentIterator {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
         }
+        let current = self.current;
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            self.current += self.step;
+        }
+        Some(current)
     }
 }
 
-impl Iterator for SegmentIterator<'_> {
+// Bresenham rasterisation of a segment, from a to b
+pub struct PixelIterator {
+    current: Point,
+    target: Point,
+    dx: i64,
+    dy: i64,
+    sx: i64,
+    sy: i64,
+    error: i64,
+    finished: bool,
+}
+
+impl Iterator for PixelIterator {
     type Item = Point;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
         let current = self.current;
-        let new = current + self.step;
-        let start = self.segment.a;
-        let target = self.segment.b;
-        // New point must be between current and target
-        let in_range = Rect::new(&start, &target).contains(&current);
-        if in_range {
-            self.current = new;
+        if current == self.target {
+            self.finished = true;
             return Some(current);
         }
-        None
+        let e2 = 2 * self.error;
+        if e2 >= self.dy {
+            self.error += self.dy;
+            self.current.x += self.sx;
+        }
+        if e2 <= self.dx {
+            self.error += self.dx;
+            self.current.y += self.sy;
+        }
+        Some(current)
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(segment: &str, raster: Raster) -> Vec<Point> {
+        segment.parse::<Segment>().unwrap().points(raster).collect()
+    }
+
+    fn list(points: &[(i64, i64)]) -> Vec<Point> {
+        points.iter().map(|&(x, y)| Point::new(x, y)).collect()
+    }
+
+    fn segment(s: &str) -> Segment {
+        s.parse().unwrap()
+    }
+
+    fn intersection(s1: &str, s2: &str) -> Option<Intersection> {
+        let result = segment(s1).intersection(&segment(s2));
+        assert_eq!(
+            result,
+            segment(s2).intersection(&segment(s1)),
+            "{} and {}",
+            s1,
+            s2
+        );
+        result
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            segment("0,9 -> 5,9"),
+            Segment::new(&Point::new(0, 9), &Point::new(5, 9))
+        );
+        assert_eq!(segment(" 0 , 9->5,9\t"), segment("0,9 -> 5,9"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let parse = |s: &str| s.parse::<Segment>().err();
+        assert_eq!(parse(""), Some(ParseSegmentError::MissingArrow));
+        assert_eq!(parse("0,9 5,9"), Some(ParseSegmentError::MissingArrow));
+        assert_eq!(parse("0,9 - > 5,9"), Some(ParseSegmentError::MissingArrow));
+        assert_eq!(
+            parse("0,9 -> 5,9 -> 1,1"),
+            Some(ParseSegmentError::ExtraArrow)
+        );
+        assert_eq!(
+            parse(" -> 5,9"),
+            Some(ParseSegmentError::InvalidStart(ParsePointError::Empty))
+        );
+        assert_eq!(
+            parse("0,9 ->"),
+            Some(ParseSegmentError::InvalidEnd(ParsePointError::Empty))
+        );
+        assert_eq!(
+            parse("0;9 -> 5,9"),
+            Some(ParseSegmentError::InvalidStart(
+                ParsePointError::MissingComma
+            ))
+        );
+        assert_eq!(
+            parse("0,9 -> 5,9,2"),
+            Some(ParseSegmentError::InvalidEnd(ParsePointError::ExtraField(
+                String::from(",2")
+            )))
+        );
+        assert!(matches!(
+            parse("0,9 -> 5,9 junk"),
+            Some(ParseSegmentError::InvalidEnd(ParsePointError::InvalidY(_)))
+        ));
+        let error = "0,9 -> x,9".parse::<Segment>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid end point: invalid x coordinate: invalid digit found in string"
+        );
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn crossing_segments() {
+        let lattice = |x, y| Some(Intersection::Lattice(Point::new(x, y)));
+        assert_eq!(intersection("0,0 -> 4,4", "0,4 -> 4,0"), lattice(2, 2));
+        assert_eq!(
+            intersection("0,0 -> 3,3", "0,1 -> 1,0"),
+            Some(Intersection::Between(0.5, 0.5))
+        );
+        assert_eq!(intersection("0,0 -> 6,3", "2,-5 -> 2,5"), lattice(2, 1));
+        // Touching at an end
+        assert_eq!(intersection("0,0 -> 2,0", "2,0 -> 2,7"), lattice(2, 0));
+        assert_eq!(intersection("0,0 -> 2,0", "3,-1 -> 3,1"), None);
+        assert_eq!(intersection("0,0 -> 4,4", "0,1 -> 4,5"), None);
+        assert!(segment("0,0 -> 6,3").contains(&Point::new(4, 2)));
+        assert!(!segment("0,0 -> 6,3").contains(&Point::new(8, 4)));
+    }
+
+    #[test]
+    fn collinear_segments() {
+        let overlap = |s: &str| Some(Intersection::Overlap(segment(s)));
+        assert_eq!(
+            intersection("0,0 -> 6,3", "8,4 -> 2,1"),
+            overlap("2,1 -> 6,3")
+        );
+        assert_eq!(
+            intersection("5,1 -> 0,1", "3,1 -> 9,1"),
+            overlap("3,1 -> 5,1")
+        );
+        assert_eq!(
+            intersection("2,9 -> 2,0", "2,4 -> 2,6"),
+            overlap("2,4 -> 2,6")
+        );
+        assert_eq!(
+            intersection("0,0 -> 2,2", "2,2 -> 5,5"),
+            Some(Intersection::Lattice(Point::new(2, 2)))
+        );
+        assert_eq!(intersection("0,0 -> 2,2", "3,3 -> 5,5"), None);
+        // Single points
+        assert_eq!(
+            intersection("1,1 -> 1,1", "0,0 -> 3,3"),
+            Some(Intersection::Lattice(Point::new(1, 1)))
+        );
+        assert_eq!(intersection("1,2 -> 1,2", "0,0 -> 3,3"), None);
+        assert_eq!(
+            intersection("1,2 -> 1,2", "1,2 -> 1,2"),
+            Some(Intersection::Lattice(Point::new(1, 2)))
+        );
+    }
+
+    #[test]
+    fn lattice_points() {
+        assert_eq!(
+            points("0,0 -> 6,4", Raster::Lattice),
+            list(&[(0, 0), (3, 2), (6, 4)])
+        );
+        assert_eq!(points("5,1 -> -1,1", Raster::Lattice).len(), 7);
+        assert_eq!(
+            points("0,0 -> 3,5", Raster::Lattice),
+            list(&[(0, 0), (3, 5)])
+        );
+        assert_eq!(points("2,2 -> 2,2", Raster::Lattice), list(&[(2, 2)]));
+        assert_eq!(
+            points("3,3 -> 0,0", Raster::Lattice),
+            list(&[(3, 3), (2, 2), (1, 1), (0, 0)])
+        );
+    }
+
+    #[test]
+    fn bresenham_pixels() {
+        assert_eq!(
+            points("0,0 -> 4,2", Raster::Bresenham),
+            list(&[(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)])
+        );
+        assert_eq!(
+            points("0,0 -> 1,3", Raster::Bresenham),
+            list(&[(0, 0), (0, 1), (1, 2), (1, 3)])
+        );
+        assert_eq!(points("4,4 -> 4,4", Raster::Bresenham), list(&[(4, 4)]));
+        // Both modes agree on horizontal, vertical and diagonal segments
+        for segment in ["0,0 -> 5,0", "3,7 -> 3,-2", "1,1 -> -4,6", "2,0 -> 8,6"] {
+            assert_eq!(
+                points(segment, Raster::Bresenham),
+                points(segment, Raster::Lattice),
+                "{}",
+                segment
+            );
+        }
+        for segment in ["0,0 -> 7,3", "-2,5 -> 3,-8", "9,1 -> 0,0"] {
+            let parsed: Segment = segment.parse().unwrap();
+            let pixels = points(segment, Raster::Bresenham);
+            assert_eq!(
+                pixels.len() as u64,
+                parsed.point_count(Raster::Bresenham),
+                "{}",
+                segment
+            );
+            assert_eq!((pixels[0], pixels[pixels.len() - 1]), (parsed.a, parsed.b));
+        }
     }
 }
@@ -0,0 +1,185 @@
+use std::cmp::Ordering;
+use std::fmt::Formatter;
+use std::ops::{Add, AddAssign, Mul, SubAssign};
+
+// Unsigned integer of any size, enough to count fish over long horizons
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BigUint {
+    // Base 2^32 digits, least significant first, without trailing zeros
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> BigUint {
+        BigUint { limbs: Vec::new() }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+
+    // Divides in place, returning the remainder
+    pub fn div_rem_small(&mut self, divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+        for limb in self.limbs.iter_mut().rev() {
+            let value = (remainder << 32) | *limb as u64;
+            *limb = (value / divisor as u64) as u32;
+            remainder = value % divisor as u64;
+        }
+        self.trim();
+        remainder as u32
+    }
+
+    pub fn rem(&self, modulus: u64) -> u64 {
+        self.limbs.iter().rev()
+            .fold(0u128, |remainder, &limb| ((remainder << 32) | limb as u128) % modulus as u128) as u64
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> BigUint {
+        let mut number = BigUint { limbs: vec![value as u32, (value >> 32) as u32] };
+        number.trim();
+        number
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, rhs: &BigUint) {
+        if self.limbs.len() < rhs.limbs.len() {
+            self.limbs.resize(rhs.limbs.len(), 0);
+        }
+        let mut carry = 0u64;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let sum = *limb as u64 + *rhs.limbs.get(i).unwrap_or(&0) as u64 + carry;
+            *limb = sum as u32;
+            carry = sum >> 32;
+            if carry == 0 && i >= rhs.limbs.len() {
+                break;
+            }
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+}
+
+impl SubAssign<&BigUint> for BigUint {
+    fn sub_assign(&mut self, rhs: &BigUint) {
+        assert!(*self >= *rhs, "Subtraction would be negative");
+        let mut borrow = 0i64;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let difference = *limb as i64 - *rhs.limbs.get(i).unwrap_or(&0) as i64 - borrow;
+            borrow = (difference < 0) as i64;
+            *limb = (difference + (borrow << 32)) as u32;
+        }
+        self.trim();
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: &BigUint) -> BigUint {
+        let mut sum = self.clone();
+        sum += rhs;
+        sum
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: &BigUint) -> BigUint {
+        if self.is_zero() || rhs.is_zero() {
+            return BigUint::zero();
+        }
+        let mut limbs = vec![0u32; self.limbs.len() + rhs.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in rhs.limbs.iter().enumerate() {
+                let value = limbs[i + j] as u64 + a as u64 * b as u64 + carry;
+                limbs[i + j] = value as u32;
+                carry = value >> 32;
+            }
+            limbs[i + rhs.limbs.len()] = carry as u32;
+        }
+        let mut product = BigUint { limbs };
+        product.trim();
+        product
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &BigUint) -> Ordering {
+        self.limbs.len().cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for BigUint {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        // Peel off groups of 9 decimal digits
+        const GROUP: u32 = 1_000_000_000;
+        let mut rest = self.clone();
+        let mut groups = Vec::new();
+        while !rest.is_zero() {
+            groups.push(rest.div_rem_small(GROUP));
+        }
+        let mut digits = groups.pop().unwrap_or(0).to_string();
+        for group in groups.iter().rev() {
+            digits.push_str(&format!("{:09}", group));
+        }
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = BigUint::from(u64::MAX);
+        let b = BigUint::from(1);
+        assert_eq!((&a + &b).to_string(), "18446744073709551616");
+        assert_eq!((&a * &a).to_string(), "340282366920938463426481119284349108225");
+        assert_eq!((&a * &BigUint::zero()), BigUint::zero());
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(format!("{:>5}", BigUint::from(42)), "   42");
+        // 10^30 has groups of zeros
+        let big = BigUint::from(1_000_000_000_000_000);
+        assert_eq!((&big * &big).to_string(), format!("1{}", "0".repeat(30)));
+        assert_eq!((&big * &big).rem(1_000_000_007), 999_657_007);
+        let mut difference = &a + &b;
+        difference -= &BigUint::from(2);
+        assert_eq!(difference, BigUint::from(u64::MAX - 1));
+        difference -= &difference.clone();
+        assert!(difference.is_zero());
+        let mut quotient = BigUint::from(1_000_000_000_007);
+        assert_eq!(quotient.div_rem_small(10), 7);
+        assert_eq!(quotient, BigUint::from(100_000_000_000));
+    }
+
+    #[test]
+    fn ordering() {
+        let small = BigUint::from(5);
+        let large = &BigUint::from(u64::MAX) * &BigUint::from(3);
+        assert!(small < large);
+        assert!(large > BigUint::from(u64::MAX));
+        assert_eq!(large.cmp(&large.clone()), Ordering::Equal);
+    }
+}
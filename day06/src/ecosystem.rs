@@ -0,0 +1,203 @@
+use std::fmt::Formatter;
+
+use crate::bigint::BigUint;
+use crate::lanternfish::{Record, School};
+
+// Each day, this fraction of the fish of a region with one of the countdowns swims to another region
+#[derive(Clone, Debug, PartialEq)]
+pub struct Migration {
+    pub from: usize,
+    pub to: usize,
+    pub countdowns: Vec<u8>,
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+pub struct Region {
+    pub name: String,
+    pub school: School,
+}
+
+// Schools of a single species in separate regions, exchanging fish after every day
+pub struct Ecosystem {
+    regions: Vec<Region>,
+    migrations: Vec<Migration>,
+}
+
+// Population of every region on some day
+pub struct Report {
+    pub day: u64,
+    pub regions: Vec<(String, Record)>,
+}
+
+impl Ecosystem {
+    pub fn new() -> Ecosystem {
+        Ecosystem { regions: Vec::new(), migrations: Vec::new() }
+    }
+
+    // Returns the index of the region, used by migrations. Regions advance together, so they must all have reached the
+    // same day. Their schools do not record their history, which would grow without limit.
+    pub fn add_region(&mut self, name: &str, mut school: School) -> usize {
+        if let Some(region) = self.regions.first() {
+            assert_eq!(region.school.species(), school.species(), "Regions must hold the same species");
+            assert_eq!(region.school.day(), school.day(), "Regions must be on the same day");
+        }
+        school.stop_recording();
+        self.regions.push(Region { name: name.to_string(), school });
+        self.regions.len() - 1
+    }
+
+    pub fn add_migration(&mut self, migration: Migration) {
+        assert!(migration.from < self.regions.len(), "Unknown region {}", migration.from);
+        assert!(migration.to < self.regions.len(), "Unknown region {}", migration.to);
+        assert!(migration.denominator > 0, "Empty fraction");
+        assert!(migration.numerator <= migration.denominator, "Cannot move more fish than there are");
+        let timers = self.regions[migration.from].school.species().timers();
+        for &countdown in &migration.countdowns {
+            assert!((countdown as usize) < timers, "Invalid countdown {}", countdown);
+        }
+        self.migrations.push(migration);
+    }
+
+    // Migrations leave in order, each from the fish left by the previous ones, and all arrive at the end of the day
+    pub fn advance_day(&mut self) {
+        for region in &mut self.regions {
+            region.school.advance_day();
+        }
+        let mut arrivals = Vec::new();
+        for migration in &self.migrations {
+            for &countdown in &migration.countdowns {
+                let migrants =
+                    self.regions[migration.from].school.emigrate(countdown, migration.numerator, migration.denominator);
+                arrivals.push((migration.to, countdown, migrants));
+            }
+        }
+        for (to, countdown, migrants) in arrivals {
+            self.regions[to].school.immigrate(countdown, &migrants);
+        }
+    }
+
+    pub fn size(&self) -> BigUint {
+        let mut count = BigUint::zero();
+        for region in &self.regions {
+            count += &region.school.size();
+        }
+        count
+    }
+
+    pub fn report(&self) -> Report {
+        let day = self.regions.first().map_or(0, |region| region.school.day());
+        let regions = self.regions.iter().map(|region| (region.name.clone(), region.school.record())).collect();
+        Report { day, regions }
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "Day {}", self.day)?;
+        for (name, record) in &self.regions {
+            let distribution: Vec<String> = record.distribution.iter().map(|nfish| nfish.to_string()).collect();
+            writeln!(f, "  {:<12} {:>20} fish [{}]", name, record.size(), distribution.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lanternfish::Species;
+
+    fn school(species: &Species, countdowns: &[u8]) -> School {
+        let mut school = School::new(species.clone());
+        for &countdown in countdowns {
+            school.add_fish(countdown, 1);
+        }
+        school
+    }
+
+    fn sizes(ecosystem: &Ecosystem) -> Vec<String> {
+        ecosystem.report().regions.iter().map(|(_, record)| record.size().to_string()).collect()
+    }
+
+    #[test]
+    fn isolated_regions() {
+        let species = Species::lanternfish();
+        let mut ecosystem = Ecosystem::new();
+        ecosystem.add_region("a", school(&species, &[3, 4, 3, 1, 2]));
+        ecosystem.add_region("b", school(&species, &[3, 4, 3, 1, 2]));
+        for _ in 0..18 {
+            ecosystem.advance_day();
+        }
+        assert_eq!(sizes(&ecosystem), ["26", "26"]);
+        assert_eq!(ecosystem.size(), BigUint::from(52));
+    }
+
+    #[test]
+    fn migration() {
+        // Fish that never spawn, so only migrations change the regions
+        let species = Species { offspring: 0, ..Species::lanternfish() };
+        let mut ecosystem = Ecosystem::new();
+        let a = ecosystem.add_region("a", school(&species, &[1; 10]));
+        let b = ecosystem.add_region("b", School::new(species.clone()));
+        ecosystem.add_migration(Migration { from: a, to: b, countdowns: vec![0], numerator: 1, denominator: 3 });
+        // Half of the fish of b swim back, rounded down
+        ecosystem.add_migration(Migration { from: b, to: a, countdowns: vec![0], numerator: 1, denominator: 2 });
+        ecosystem.advance_day();
+        // 3 of the 10 fish leave a, while b had none to send back yet
+        assert_eq!(sizes(&ecosystem), ["7", "3"]);
+        let report = ecosystem.report();
+        assert_eq!(report.day, 1);
+        assert_eq!(report.regions[1].1.distribution[0], BigUint::from(3));
+        // Nobody moves until the fish are back at countdown 0, on day 8
+        for _ in 0..6 {
+            ecosystem.advance_day();
+        }
+        assert_eq!(sizes(&ecosystem), ["7", "3"]);
+        ecosystem.advance_day();
+        // a sends 2 of 7, b sends 1 of 3
+        assert_eq!(sizes(&ecosystem), ["6", "4"]);
+        assert_eq!(ecosystem.size(), BigUint::from(10));
+    }
+
+    #[test]
+    fn report() {
+        let species = Species::lanternfish();
+        let mut ecosystem = Ecosystem::new();
+        ecosystem.add_region("reef", school(&species, &[0, 1]));
+        ecosystem.advance_day();
+        assert_eq!(ecosystem.report().to_string(), format!("Day 1\n  reef {:>28} fish [1,0,0,0,0,0,1,0,1]\n", 3));
+    }
+
+    #[test]
+    fn no_history() {
+        let mut ecosystem = Ecosystem::new();
+        ecosystem.add_region("a", school(&Species::lanternfish(), &[3, 4, 3, 1, 2]));
+        for _ in 0..100 {
+            ecosystem.advance_day();
+        }
+        let series = ecosystem.regions[0].school.series();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].day, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Regions must be on the same day")]
+    fn regions_on_different_days() {
+        let species = Species::lanternfish();
+        let mut ecosystem = Ecosystem::new();
+        ecosystem.add_region("a", School::new(species.clone()));
+        let mut later = School::new(species);
+        later.advance_day();
+        ecosystem.add_region("b", later);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown region")]
+    fn unknown_region() {
+        let mut ecosystem = Ecosystem::new();
+        ecosystem.add_region("a", School::new(Species::lanternfish()));
+        ecosystem.add_migration(Migration { from: 0, to: 1, countdowns: vec![0], numerator: 1, denominator: 2 });
+    }
+}
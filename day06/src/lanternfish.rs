@@ -0,0 +1,473 @@
+use crate::bigint::BigUint;
+use crate::matrix::{Matrix, Modular};
+
+// Lifecycle shared by all the fish of a school
+#[derive(Clone, Debug, PartialEq)]
+pub struct Species {
+    // Countdown restarting from this value after spawning
+    pub reset_timer: u8,
+    // Countdown of newborn fish
+    pub newborn_timer: u8,
+    // Fish older than this many days die, if set
+    pub max_age: Option<u32>,
+    // Fish born each time the countdown expires
+    pub offspring: u64,
+}
+
+impl Species {
+    pub fn lanternfish() -> Species {
+        Species {
+            reset_timer: 6,
+            newborn_timer: 8,
+            max_age: None,
+            offspring: 1,
+        }
+    }
+
+    // Number of distinct countdown values
+    pub fn timers(&self) -> usize {
+        self.reset_timer.max(self.newborn_timer) as usize + 1
+    }
+
+    // Number of distinct ages tracked, a single one when fish live forever
+    pub fn ages(&self) -> usize {
+        self.max_age.map_or(1, |max_age| max_age as usize + 1)
+    }
+}
+
+// Fish of a school for each countdown, whatever their age, on some day
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub day: u64,
+    pub distribution: Vec<BigUint>,
+}
+
+impl Record {
+    pub fn size(&self) -> BigUint {
+        let mut count = BigUint::zero();
+        for nfish in &self.distribution {
+            count += nfish;
+        }
+        count
+    }
+}
+
+// Fish are only counted by countdown, and by age when they can die
+pub struct School {
+    species: Species,
+    // Number of fish for each age and countdown, ages first
+    fish: Vec<BigUint>,
+    // Days elapsed since the school was created
+    day: u64,
+    // Past days, without the days skipped by fast-forwarding
+    history: Vec<Record>,
+    recording: bool,
+}
+
+impl School {
+    pub fn new(species: Species) -> Self {
+        let buckets = species.ages() * species.timers();
+        Self {
+            species,
+            fish: vec![BigUint::zero(); buckets],
+            day: 0,
+            history: Vec::new(),
+            recording: true,
+        }
+    }
+
+    fn bucket(&self, age: usize, countdown: usize) -> usize {
+        age * self.species.timers() + countdown
+    }
+
+    // Where the fish of each bucket go in a day, as (from, to, fish per fish)
+    fn moves(&self) -> Vec<(usize, usize, u64)> {
+        let timers = self.species.timers();
+        let ages = self.species.ages();
+        let mut moves = Vec::new();
+        for bucket in 0..self.fish.len() {
+            let (age, countdown) = (bucket / timers, bucket % timers);
+            // Fish of the last age die, only aging when ages are tracked
+            let next_age = if self.species.max_age.is_none() { 0 } else { age + 1 };
+            if next_age >= ages {
+                continue;
+            }
+            // Move fish to new countdown
+            if countdown > 0 {
+                moves.push((bucket, self.bucket(next_age, countdown - 1), 1));
+            } else {
+                moves.push((bucket, self.bucket(next_age, self.species.reset_timer as usize), 1));
+                moves.push((bucket, self.bucket(0, self.species.newborn_timer as usize), self.species.offspring));
+            }
+        }
+        moves
+    }
+
+    // Linear map from the fish of a day to the fish of the next day
+    pub fn transition(&self) -> Matrix<u64> {
+        let mut transition = Matrix::from_fn(self.fish.len(), |_, _| 0);
+        for (from, to, n) in self.moves() {
+            *transition.get_mut(to, from) += n;
+        }
+        transition
+    }
+
+    pub fn advance_day(&mut self) {
+        if self.recording {
+            self.history.push(self.record());
+        }
+        self.step();
+    }
+
+    // Advances by a day without recording the day left behind
+    fn step(&mut self) {
+        self.day += 1;
+        let mut next_day_fish = vec![BigUint::zero(); self.fish.len()];
+        for (from, to, n) in self.moves() {
+            if n == 1 {
+                next_day_fish[to] += &self.fish[from];
+            } else {
+                next_day_fish[to] += &(&self.fish[from] * &BigUint::from(n));
+            }
+        }
+        self.fish = next_day_fish;
+    }
+
+    // Advances by raising the transition to the n-th power, in O(log n) matrix products
+    pub fn advance_days(&mut self, n: u64) {
+        if n == 0 {
+            return;
+        }
+        if self.recording {
+            self.history.push(self.record());
+        }
+        self.day += n;
+        let transition = self.transition().map(|&entry| BigUint::from(entry)).pow(n);
+        self.fish = transition.apply(&self.fish);
+    }
+
+    // Size of the school after n more days, modulo some number, without changing it. Works for any horizon.
+    pub fn size_after_mod(&self, n: u64, modulus: u64) -> u64 {
+        let transition = self.transition().map(|&entry| Modular::new(entry, modulus)).pow(n);
+        let fish: Vec<Modular> = self.fish.iter().map(|nfish| Modular::new(nfish.rem(modulus), modulus)).collect();
+        transition.apply(&fish).iter().fold(0, |sum, nfish| (sum + nfish.value) % modulus)
+    }
+
+    // Growth per day in the long run, as the dominant eigenvalue of the transition. Found by power iteration on the
+    // transition plus the identity, which has the same dominant eigenvector but cannot oscillate.
+    pub fn growth_rate(&self) -> f64 {
+        const ITERATIONS: usize = 100_000;
+        const TOLERANCE: f64 = 1e-12;
+        let transition = self.transition().map(|&entry| entry as f64);
+        let mut vector = vec![1.0 / self.fish.len() as f64; self.fish.len()];
+        let mut rate = 0.0;
+        for _ in 0..ITERATIONS {
+            let mut next = transition.apply(&vector);
+            for (next, current) in next.iter_mut().zip(&vector) {
+                *next += current;
+            }
+            let norm: f64 = next.iter().sum();
+            let next_rate = norm - 1.0;
+            vector = next.iter().map(|value| value / norm).collect();
+            let converged = (next_rate - rate).abs() < TOLERANCE;
+            rate = next_rate;
+            if converged {
+                break;
+            }
+        }
+        rate
+    }
+
+    // First day, up to the limit, when the school has more fish than the threshold. Schools that cannot die only
+    // grow, so they are searched by doubling then halving steps of days, others are stepped day by day until they
+    // exceed the threshold or start repeating themselves.
+    pub fn first_day_exceeding(&self, threshold: &BigUint, limit: u64) -> Option<u64> {
+        if self.size() > *threshold {
+            return Some(self.day);
+        }
+        if self.species.max_age.is_some() {
+            let mut school = School {
+                species: self.species.clone(),
+                fish: self.fish.clone(),
+                day: self.day,
+                history: Vec::new(),
+                recording: false,
+            };
+            // A school that stays small ends up repeating itself, which is spotted by comparing it with its state
+            // 1, 2, 4... days earlier (Brent's cycle detection)
+            let mut saved = school.fish.clone();
+            let (mut period, mut since_saved) = (1, 0);
+            while school.day < self.day.saturating_add(limit) {
+                school.step();
+                if school.size() > *threshold {
+                    return Some(school.day);
+                }
+                if school.fish == saved {
+                    return None;
+                }
+                since_saved += 1;
+                if since_saved == period {
+                    saved = school.fish.clone();
+                    (period, since_saved) = (2 * period, 0);
+                }
+            }
+            return None;
+        }
+        // Without fish or offspring nothing ever changes
+        if self.size() == BigUint::zero() || self.species.offspring == 0 {
+            return None;
+        }
+        let count = |step: &Matrix<BigUint>, fish: &[BigUint]| {
+            let mut count = BigUint::zero();
+            for nfish in step.apply(fish) {
+                count += &nfish;
+            }
+            count
+        };
+        // Steps of 1, 2, 4... days, until one goes past the threshold
+        let mut steps = vec![self.transition().map(|&entry| BigUint::from(entry))];
+        let mut previous = self.size();
+        loop {
+            let reached = count(&steps[steps.len() - 1], &self.fish);
+            if reached > *threshold {
+                break;
+            }
+            let days = match 1u64.checked_shl(steps.len() as u32 - 1) {
+                Some(days) if days < limit => days,
+                _ => return None,
+            };
+            // Every fish spawns within that many days, so a school that did not grow never will
+            if reached == previous && days / 2 >= self.species.timers() as u64 {
+                return None;
+            }
+            previous = reached;
+            let step = &steps[steps.len() - 1];
+            steps.push(step.mul(step));
+        }
+        // Take the largest steps that stay below the threshold
+        let mut fish = self.fish.clone();
+        let mut days = 0;
+        for (k, step) in steps.iter().enumerate().rev() {
+            if count(step, &fish) <= *threshold {
+                fish = step.apply(&fish);
+                days += 1 << k;
+            }
+        }
+        (days < limit).then(|| self.day + days + 1)
+    }
+
+    // Fish for each countdown, whatever their age
+    pub fn distribution(&self) -> Vec<BigUint> {
+        let timers = self.species.timers();
+        let mut distribution = vec![BigUint::zero(); timers];
+        for (bucket, nfish) in self.fish.iter().enumerate() {
+            distribution[bucket % timers] += nfish;
+        }
+        distribution
+    }
+
+    pub fn day(&self) -> u64 {
+        self.day
+    }
+
+    // Forgets the past days and stops recording new ones, for schools simulated over long periods
+    pub fn stop_recording(&mut self) {
+        self.history = Vec::new();
+        self.recording = false;
+    }
+
+    pub fn record(&self) -> Record {
+        Record { day: self.day, distribution: self.distribution() }
+    }
+
+    // Every day recorded so far, ending with the current one. Days skipped by `advance_days` are missing, so records
+    // should be told apart by their day rather than their index.
+    pub fn series(&self) -> Vec<Record> {
+        let mut series = self.history.clone();
+        series.push(self.record());
+        series
+    }
+
+    pub fn species(&self) -> &Species {
+        &self.species
+    }
+
+    // Removes a fraction of the fish with the given countdown, rounded down, and returns them by age
+    pub fn emigrate(&mut self, countdown: u8, numerator: u32, denominator: u32) -> Vec<BigUint> {
+        assert!(numerator <= denominator, "Cannot move more fish than there are");
+        let mut migrants = Vec::with_capacity(self.species.ages());
+        for age in 0..self.species.ages() {
+            let bucket = self.bucket(age, countdown as usize);
+            let mut moving = &self.fish[bucket] * &BigUint::from(numerator as u64);
+            moving.div_rem_small(denominator);
+            self.fish[bucket] -= &moving;
+            migrants.push(moving);
+        }
+        migrants
+    }
+
+    // Adds fish leaving another school of the same species, keeping their ages
+    pub fn immigrate(&mut self, countdown: u8, migrants: &[BigUint]) {
+        assert_eq!(migrants.len(), self.species.ages(), "Migrants must come from the same species");
+        for (age, nfish) in migrants.iter().enumerate() {
+            let bucket = self.bucket(age, countdown as usize);
+            self.fish[bucket] += nfish;
+        }
+    }
+
+    // Adds fish of age 0 with the given countdown
+    pub fn add_fish(&mut self, countdown: u8, nfish: u64) {
+        assert!((countdown as usize) < self.species.timers(), "Invalid countdown {}", countdown);
+        let bucket = self.bucket(0, countdown as usize);
+        self.fish[bucket] += &BigUint::from(nfish);
+    }
+
+    pub fn size(&self) -> BigUint {
+        let mut count = BigUint::zero();
+        for nfish in &self.fish {
+            count += nfish;
+        }
+        count
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example(species: Species) -> School {
+        let mut school = School::new(species);
+        for countdown in [3, 4, 3, 1, 2] {
+            school.add_fish(countdown, 1);
+        }
+        school
+    }
+
+    // Size of the school after stepping through the days
+    fn advance(school: &mut School, days: u64) -> BigUint {
+        for _ in 0..days {
+            school.advance_day();
+        }
+        school.size()
+    }
+
+    #[test]
+    fn fast_forward_matches_daily_steps() {
+        for species in [
+            Species::lanternfish(),
+            Species { reset_timer: 2, newborn_timer: 4, max_age: Some(9), offspring: 3 },
+        ] {
+            let mut daily = example(species.clone());
+            let mut fast = example(species);
+            let modulus = 1_000_003;
+            let expected_mod = fast.size_after_mod(1000, modulus);
+            for _ in 0..1000 {
+                daily.advance_day();
+            }
+            fast.advance_days(1000);
+            assert_eq!(fast.size(), daily.size());
+            assert_eq!(expected_mod, daily.size().rem(modulus));
+        }
+        // Far beyond u64
+        let mut school = example(Species::lanternfish());
+        school.advance_days(2000);
+        assert!(school.size() > BigUint::from(u64::MAX));
+    }
+
+    #[test]
+    fn series() {
+        let mut school = example(Species::lanternfish());
+        school.advance_day();
+        school.advance_day();
+        school.advance_days(16);
+        // Nothing happens in zero days, not even a record
+        school.advance_days(0);
+        let series = school.series();
+        let days: Vec<u64> = series.iter().map(|record| record.day).collect();
+        assert_eq!(days, [0, 1, 2, 18]);
+        let sizes: Vec<String> = series.iter().map(|record| record.size().to_string()).collect();
+        assert_eq!(sizes, ["5", "5", "6", "26"]);
+        // Day 2 of the puzzle: 1,2,1,6,0,8
+        let distribution: Vec<String> = series[2].distribution.iter().map(|nfish| nfish.to_string()).collect();
+        assert_eq!(distribution, ["1", "2", "1", "0", "0", "0", "1", "0", "1"]);
+    }
+
+    #[test]
+    fn growth_rate() {
+        // Lanternfish populations multiply by the real root of x^9 = x^2 + 1
+        let rate = example(Species::lanternfish()).growth_rate();
+        assert!((rate.powi(9) - rate.powi(2) - 1.0).abs() < 1e-9);
+        assert!(rate > 1.09 && rate < 1.1);
+        // Each fish only replaces itself before dying
+        let species = Species { reset_timer: 1, newborn_timer: 1, max_age: Some(3), offspring: 1 };
+        assert!((School::new(species).growth_rate() - 1.0).abs() < 1e-9);
+        // Fish that never spawn live on, unless they die on their first day
+        let species = Species { offspring: 0, ..Species::lanternfish() };
+        assert!((School::new(species.clone()).growth_rate() - 1.0).abs() < 1e-9);
+        let species = Species { max_age: Some(0), ..species };
+        assert!(School::new(species).growth_rate().abs() < 1e-9);
+    }
+
+    #[test]
+    fn first_day_exceeding() {
+        let school = example(Species::lanternfish());
+        assert_eq!(school.first_day_exceeding(&BigUint::from(4), 100), Some(0));
+        assert_eq!(school.first_day_exceeding(&BigUint::from(25), 100), Some(18));
+        assert_eq!(school.first_day_exceeding(&BigUint::from(26), 100), Some(19));
+        assert_eq!(school.first_day_exceeding(&BigUint::from(5933), 100), Some(80));
+        assert_eq!(school.first_day_exceeding(&BigUint::from(26984457538), 1000), Some(256));
+        assert_eq!(school.first_day_exceeding(&BigUint::from(5933), 79), None);
+        // Counted from the current day
+        let mut later = example(Species::lanternfish());
+        later.advance_days(10);
+        assert_eq!(later.first_day_exceeding(&BigUint::from(5933), 100), Some(80));
+        // Mortal schools that stop growing
+        let species = Species { reset_timer: 1, newborn_timer: 1, max_age: Some(3), offspring: 1 };
+        let mut school = School::new(species);
+        school.add_fish(1, 1);
+        assert_eq!(school.first_day_exceeding(&BigUint::from(1), 100), Some(2));
+        assert_eq!(school.first_day_exceeding(&BigUint::from(2), 100), None);
+        // Schools that stay small are given up on as soon as they repeat themselves, whatever the limit
+        assert_eq!(school.first_day_exceeding(&BigUint::from(2), u64::MAX), None);
+        let species = Species { reset_timer: 6, newborn_timer: 8, max_age: Some(2), offspring: 1 };
+        let mut dying = School::new(species);
+        dying.add_fish(3, 1);
+        assert_eq!(dying.first_day_exceeding(&BigUint::from(1), u64::MAX), None);
+        // Immortal schools that never grow
+        let mut barren = School::new(Species { offspring: 0, ..Species::lanternfish() });
+        barren.add_fish(3, 1);
+        assert_eq!(barren.first_day_exceeding(&BigUint::from(1), u64::MAX), None);
+        let empty = School::new(Species::lanternfish());
+        assert_eq!(empty.first_day_exceeding(&BigUint::zero(), u64::MAX), None);
+    }
+
+    #[test]
+    fn twins() {
+        // Two offspring per spawn: 1 fish, then 3 after day 1, then 9 after day 8
+        let species = Species { reset_timer: 6, newborn_timer: 6, max_age: None, offspring: 2 };
+        let mut school = School::new(species);
+        school.add_fish(0, 1);
+        assert_eq!(advance(&mut school, 1), BigUint::from(3));
+        assert_eq!(advance(&mut school, 7), BigUint::from(9));
+    }
+
+    #[test]
+    fn mortality() {
+        // Every fish spawns once, two days after being born, and dies before spawning again
+        let species = Species { reset_timer: 1, newborn_timer: 1, max_age: Some(3), offspring: 1 };
+        let mut school = School::new(species.clone());
+        school.add_fish(1, 1);
+        let sizes: Vec<String> = (0..6).map(|_| advance(&mut school, 1).to_string()).collect();
+        assert_eq!(sizes, ["1", "2", "2", "2", "2", "2"]);
+        // Without aging the same school grows forever
+        let mut immortal = School::new(Species { max_age: None, ..species });
+        immortal.add_fish(1, 1);
+        assert_eq!(advance(&mut immortal, 6), BigUint::from(8));
+        // Fish living a single day die before spawning, even though only one age is tracked
+        let mut mayflies = School::new(Species { max_age: Some(0), ..Species::lanternfish() });
+        mayflies.add_fish(0, 1);
+        mayflies.add_fish(3, 2);
+        assert_eq!(advance(&mut mayflies, 1), BigUint::zero());
+    }
+}
@@ -1,94 +1,163 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
-mod lanternfish {
-    const RESET_COUNTDOWN: u8 = 6;
-    const NEW_FISH_COUNTOWN: u8 = 8;
-    const COUNTDOWN_MAP_SIZE: usize = NEW_FISH_COUNTOWN as usize + 1;
+mod bigint;
+mod ecosystem;
+mod lanternfish;
+mod matrix;
 
-    pub struct School {
-        pub fish: [u64; COUNTDOWN_MAP_SIZE],
-    }
-
-    impl School {
-        pub fn new() -> Self {
-            Self { fish: [0; COUNTDOWN_MAP_SIZE] }
-        }
+use crate::bigint::BigUint;
+use crate::ecosystem::{Ecosystem, Migration};
+use crate::lanternfish::{School, Species};
 
-        pub fn advance_day(&mut self) {
-            let mut next_day_fish = self.fish.clone();
-            for (countdown, &curr_day_fish) in self.fish.iter().enumerate() {
-                // Move fish to new countdown
-                next_day_fish[countdown] -= curr_day_fish;
-                if countdown > 0 {
-                    next_day_fish[countdown - 1] += curr_day_fish;
-                } else {
-                    next_day_fish[RESET_COUNTDOWN as usize] += curr_day_fish;
-                    next_day_fish[NEW_FISH_COUNTOWN as usize] += curr_day_fish;
-                }
-            }
-            self.fish = next_day_fish;
-        }
+// Stepping day by day is cheaper than powering the transition over short horizons
+const MAX_DAILY_STEPS: u64 = 64;
 
-        pub fn add_fish(&mut self, countdown: u8, nfish: u64) {
-            self.fish[countdown as usize] += nfish;
-        }
-
-        pub fn size(&self) -> usize {
-            let mut count = 0usize;
-            for &nfish in &self.fish {
-                count += nfish as usize;
-            }
-            count
+fn simulate(school: &mut School, n: u64) -> BigUint {
+    if n <= MAX_DAILY_STEPS {
+        for _ in 0..n {
+            school.advance_day();
         }
-    }
-}
-
-use crate::lanternfish::School;
-
-fn simulate(school: &mut School, n: u64) -> usize {
-    for _ in 0..n {
-        school.advance_day();
+    } else {
+        school.advance_days(n);
     }
     school.size()
 }
 
-fn part1() {
-    println!("{:-<80}", "Part 1 ");
+fn read_school(species: &Species) -> School {
     // Read data from file
     let f = File::open("data.txt").expect("Could not open file");
     let mut reader = BufReader::new(f);
     let mut line = String::new();
-    let mut school = lanternfish::School::new();
+    let mut school = School::new(species.clone());
     reader.read_line(&mut line).expect("Could not read line");
     for countdown in line.trim().split(',') {
         let countdown = countdown.parse::<u8>().expect("Could not parse number of fish");
         school.add_fish(countdown, 1);
     }
+    school
+}
+
+fn part1(species: &Species) {
+    println!("{:-<80}", "Part 1 ");
+    let mut school = read_school(species);
     // Simulate 80 days
     const N: u64 = 80;
     println!("School size after {} days: {}", N, simulate(&mut school, N));
 }
 
-fn part2() {
+fn part2(species: &Species) {
     println!("{:-<80}", "Part 2 ");
-    // Read data from file
-    let f = File::open("data.txt").expect("Could not open file");
-    let mut reader = BufReader::new(f);
-    let mut line = String::new();
-    let mut school = lanternfish::School::new();
-    reader.read_line(&mut line).expect("Could not read line");
-    for countdown in line.trim().split(',') {
-        let countdown = countdown.parse::<u8>().expect("Could not parse number of fish");
-        school.add_fish(countdown, 1);
-    }
-
+    let mut school = read_school(species);
     // Simulate 256 days
     const N: u64 = 256;
     println!("School size after {} days: {}", N, simulate(&mut school, N));
 }
 
+// Lanternfish unless overridden with --reset, --newborn, --max-age or --offspring followed by a value
+fn read_species() -> Species {
+    let mut species = Species::lanternfish();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    for pair in args.chunks(2) {
+        let value = pair.get(1).expect("Missing value for option");
+        match pair[0].as_str() {
+            "--reset" => species.reset_timer = value.parse().expect("Invalid reset timer"),
+            "--newborn" => species.newborn_timer = value.parse().expect("Invalid newborn timer"),
+            "--max-age" => species.max_age = Some(value.parse().expect("Invalid maximum age")),
+            "--offspring" => species.offspring = value.parse().expect("Invalid number of offspring"),
+            option => panic!("Unknown option {}", option),
+        }
+    }
+    species
+}
+
+// Far beyond what exact counts can reach, only the population modulo some number is known
+fn far_future(species: &Species) {
+    println!("{:-<80}", "Far future ");
+    const N: u64 = 1_000_000_000_000;
+    const MODULUS: u64 = 1_000_000_007;
+    let school = read_school(species);
+    println!("School size after {} days, modulo {}: {}", N, MODULUS, school.size_after_mod(N, MODULUS));
+}
+
+// Day by day distribution over the first days, then how fast the school grows in the long run
+fn growth(species: &Species) {
+    println!("{:-<80}", "Growth ");
+    const DAYS: u64 = 18;
+    const THRESHOLD: u64 = 1_000_000_000_000;
+    const LIMIT: u64 = 1_000_000;
+    let mut school = read_school(species);
+    for _ in 0..DAYS {
+        school.advance_day();
+    }
+    for record in school.series() {
+        let distribution: Vec<String> = record.distribution.iter().map(|nfish| nfish.to_string()).collect();
+        println!("Day {:>2}: {:>6} fish [{}]", record.day, record.size(), distribution.join(","));
+    }
+    println!("Growth rate: {:.6} per day", school.growth_rate());
+    match school.first_day_exceeding(&BigUint::from(THRESHOLD), LIMIT) {
+        Some(day) => println!("School exceeds {} fish on day {}", THRESHOLD, day),
+        None => println!("School does not exceed {} fish within {} days", THRESHOLD, LIMIT),
+    }
+}
+
+// The school spreads from its reef to a lagoon and the open sea: a quarter of the fish about to spawn swim to the
+// lagoon every day, a tenth of the newborns in the lagoon drift out to sea, and half of those at sea come back
+fn ecosystem(species: &Species) {
+    println!("{:-<80}", "Ecosystem ");
+    const DAYS: u64 = 80;
+    const REPORT_EVERY: u64 = 20;
+    let mut ecosystem = Ecosystem::new();
+    let reef = ecosystem.add_region("Reef", read_school(species));
+    let lagoon = ecosystem.add_region("Lagoon", School::new(species.clone()));
+    let sea = ecosystem.add_region("Open sea", School::new(species.clone()));
+    ecosystem.add_migration(Migration { from: reef, to: lagoon, countdowns: vec![0], numerator: 1, denominator: 4 });
+    ecosystem.add_migration(Migration {
+        from: lagoon,
+        to: sea,
+        countdowns: vec![species.newborn_timer],
+        numerator: 1,
+        denominator: 10,
+    });
+    ecosystem.add_migration(Migration { from: sea, to: reef, countdowns: vec![0], numerator: 1, denominator: 2 });
+    for day in 1..=DAYS {
+        ecosystem.advance_day();
+        if day % REPORT_EVERY == 0 {
+            print!("{}", ecosystem.report());
+        }
+    }
+    println!("Fish in all regions after {} days: {}", DAYS, ecosystem.size());
+}
+
 fn main() {
-    part1();
-    part2();
+    let species = read_species();
+    if species != Species::lanternfish() {
+        println!("Simulating {:?}", species);
+    }
+    part1(&species);
+    part2(&species);
+    far_future(&species);
+    growth(&species);
+    ecosystem(&species);
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example(species: Species) -> School {
+        let mut school = School::new(species);
+        for countdown in [3, 4, 3, 1, 2] {
+            school.add_fish(countdown, 1);
+        }
+        school
+    }
+
+    #[test]
+    fn lanternfish() {
+        assert_eq!(simulate(&mut example(Species::lanternfish()), 18), BigUint::from(26));
+        assert_eq!(simulate(&mut example(Species::lanternfish()), 80), BigUint::from(5934));
+        assert_eq!(simulate(&mut example(Species::lanternfish()), 256), BigUint::from(26984457539));
+    }
 }
@@ -0,0 +1,173 @@
+use crate::bigint::BigUint;
+
+// Numbers that matrices can be multiplied over. Zero and one come from an existing value, which may carry
+// parameters such as a modulus.
+pub trait Ring: Clone {
+    fn zero(&self) -> Self;
+    fn one(&self) -> Self;
+    fn add(&self, other: &Self) -> Self;
+    fn mul(&self, other: &Self) -> Self;
+}
+
+impl Ring for BigUint {
+    fn zero(&self) -> Self {
+        BigUint::zero()
+    }
+
+    fn one(&self) -> Self {
+        BigUint::from(1)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+}
+
+// Approximate real numbers, for numerical estimates
+impl Ring for f64 {
+    fn zero(&self) -> Self {
+        0.0
+    }
+
+    fn one(&self) -> Self {
+        1.0
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+}
+
+// Integer modulo some number
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Modular {
+    pub value: u64,
+    pub modulus: u64,
+}
+
+impl Modular {
+    pub fn new(value: u64, modulus: u64) -> Modular {
+        Modular { value: value % modulus, modulus }
+    }
+}
+
+impl Ring for Modular {
+    fn zero(&self) -> Self {
+        Modular::new(0, self.modulus)
+    }
+
+    fn one(&self) -> Self {
+        Modular::new(1, self.modulus)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Modular::new(((self.value as u128 + other.value as u128) % self.modulus as u128) as u64, self.modulus)
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        Modular::new(((self.value as u128 * other.value as u128) % self.modulus as u128) as u64, self.modulus)
+    }
+}
+
+// Square matrix, row by row
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix<T> {
+    size: usize,
+    entries: Vec<T>,
+}
+
+impl<T> Matrix<T> {
+    pub fn from_fn<F: FnMut(usize, usize) -> T>(size: usize, mut f: F) -> Matrix<T> {
+        let entries = (0..size * size).map(|i| f(i / size, i % size)).collect();
+        Matrix { size, entries }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> &T {
+        &self.entries[row * self.size + col]
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> &mut T {
+        &mut self.entries[row * self.size + col]
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Matrix<U> {
+        Matrix { size: self.size, entries: self.entries.iter().map(f).collect() }
+    }
+}
+
+impl<T: Ring> Matrix<T> {
+    pub fn mul(&self, other: &Matrix<T>) -> Matrix<T> {
+        assert_eq!(self.size, other.size, "Matrices must have the same size");
+        Matrix::from_fn(self.size, |row, col| {
+            (1..self.size).fold(self.get(row, 0).mul(other.get(0, col)), |sum, k| {
+                sum.add(&self.get(row, k).mul(other.get(k, col)))
+            })
+        })
+    }
+
+    // Raises the matrix to the given power by repeated squaring
+    pub fn pow(&self, mut exponent: u64) -> Matrix<T> {
+        assert!(self.size > 0, "Empty matrix");
+        let zero = self.entries[0].zero();
+        let one = zero.one();
+        let mut result = Matrix::from_fn(self.size, |row, col| if row == col { one.clone() } else { zero.clone() });
+        let mut square = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&square);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                square = square.mul(&square);
+            }
+        }
+        result
+    }
+
+    pub fn apply(&self, vector: &[T]) -> Vec<T> {
+        assert_eq!(vector.len(), self.size, "Vector must match the matrix size");
+        (0..self.size).map(|row| {
+            (1..self.size).fold(self.get(row, 0).mul(&vector[0]), |sum, col| {
+                sum.add(&self.get(row, col).mul(&vector[col]))
+            })
+        }).collect()
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fibonacci<T: Ring>(one: T, n: u64) -> T {
+        let zero = one.zero();
+        let step = Matrix::from_fn(2, |row, col| if row == 1 && col == 1 { zero.clone() } else { one.clone() });
+        step.pow(n).get(0, 1).clone()
+    }
+
+    #[test]
+    fn powers() {
+        assert_eq!(fibonacci(BigUint::from(1), 0), BigUint::zero());
+        assert_eq!(fibonacci(BigUint::from(1), 10), BigUint::from(55));
+        assert_eq!(fibonacci(BigUint::from(1), 100).to_string(), "354224848179261915075");
+        assert_eq!(fibonacci(Modular::new(1, 1_000_000_007), 100).value, 687_995_182);
+        // Last digits repeat every 60 numbers, so this is the last digit of F(47) = 2971215073
+        assert_eq!(fibonacci(Modular::new(1, 10), 1_000_000_000_000_000_007).value, 3);
+    }
+
+    #[test]
+    fn apply_vector() {
+        let m = Matrix::from_fn(2, |row, col| Modular::new((row * 2 + col + 1) as u64, 100));
+        let v = m.apply(&[Modular::new(1, 100), Modular::new(10, 100)]);
+        assert_eq!(v.iter().map(|x| x.value).collect::<Vec<u64>>(), vec![21, 43]);
+        assert_eq!(m.map(|x| x.value).size, 2);
+    }
+}
@@ -1,8 +1,11 @@
-use std::collections::HashMap;
 use std::error::Error;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use crate::CostMode::{Linear, Quadratic};
+
+mod solver;
+
+use crate::solver::align;
+use crate::solver::CostMode::{Convex, Custom, Linear, Quadratic};
 
 fn read_data(fname: &str) -> Result<Vec<i64>, Box<dyn Error>> {
     let f = File::open(fname)?;
@@ -17,61 +20,39 @@ fn read_data(fname: &str) -> Result<Vec<i64>, Box<dyn Error>> {
     Ok(data)
 }
 
-#[derive(Copy, Clone)]
-enum CostMode {
-    Linear,
-    Quadratic,
-}
-
-fn fuel_cost(pos: i64, target: i64, mode: CostMode) -> i64 {
-    let distance = (target - pos).abs();
-    match mode {
-        CostMode::Linear => distance,
-        CostMode::Quadratic => distance * (distance + 1) / 2
-    }
-}
-
-fn total_fuel_cost(positions: &Vec<i64>, target: i64, mode: CostMode) -> i64 {
-    let mut cost = 0;
-    for &pos in positions {
-        cost += fuel_cost(pos, target, mode);
-    }
-    cost
-}
-
 fn part1() {
     println!("{:-<80}", "Part 1 ");
     let positions = read_data("data.txt").expect("Could not read data");
-    let pmin = *positions.iter().min().unwrap();
-    let pmax = *positions.iter().max().unwrap();
-    let mut costs = HashMap::with_capacity((pmax - pmin) as usize);
-    for pos in pmin..=pmax {
-        costs.insert(pos, total_fuel_cost(&positions, pos, Linear));
-    }
-    // Find minimum pair
-    let min_pos_cost = costs.iter().reduce(|p1, p2| if p1.1 < p2.1 {p1} else {p2}).expect("Could not reduce");
-    let min_pos  = *min_pos_cost.0;
-    let min_cost = *min_pos_cost.1;
-    println!("Minimum cost is {} at pos {}", min_cost, min_pos);
+    let alignment = align(&positions, Linear).expect("No crabs");
+    println!("Minimum cost is {} at pos {}", alignment.cost, alignment.position);
 }
 
 fn part2() {
     println!("{:-<80}", "Part 2 ");
     let positions = read_data("data.txt").expect("Could not read data");
-    let pmin = *positions.iter().min().unwrap();
-    let pmax = *positions.iter().max().unwrap();
-    let mut costs = HashMap::with_capacity((pmax - pmin) as usize);
-    for pos in pmin..=pmax {
-        costs.insert(pos, total_fuel_cost(&positions, pos, Quadratic));
-    }
-    // Find minimum pair
-    let min_pos_cost = costs.iter().reduce(|p1, p2| if p1.1 < p2.1 {p1} else {p2}).expect("Could not reduce");
-    let min_pos  = *min_pos_cost.0;
-    let min_cost = *min_pos_cost.1;
-    println!("Minimum cost is {} at pos {}", min_cost, min_pos);
+    let alignment = align(&positions, Quadratic).expect("No crabs");
+    println!("Minimum cost is {} at pos {}", alignment.cost, alignment.position);
+}
+
+// Crabs whose engines burn the cube of the distance, whose cheapest position is found by bisection
+fn cubic() {
+    println!("{:-<80}", "Cubic cost ");
+    let positions = read_data("data.txt").expect("Could not read data");
+    let alignment = align(&positions, Convex(|distance| distance.pow(3))).expect("No crabs");
+    println!("Minimum cost is {} at pos {}", alignment.cost, alignment.position);
+}
+
+// Crabs whose engines burn the integer square root of the distance, so that every position has to be tried
+fn concave() {
+    println!("{:-<80}", "Concave cost ");
+    let positions = read_data("data.txt").expect("Could not read data");
+    let alignment = align(&positions, Custom(|distance| distance.isqrt())).expect("No crabs");
+    println!("Minimum cost is {} at pos {}", alignment.cost, alignment.position);
 }
 
 fn main() {
     part1();
     part2();
+    cubic();
+    concave();
 }
@@ -0,0 +1,154 @@
+#[derive(Copy, Clone)]
+pub enum CostMode {
+    Linear,
+    Quadratic,
+    // A fuel cost that never decreases with the distance and whose increments never shrink
+    Convex(fn(i64) -> i64),
+    // Any fuel cost for a distance, without assuming anything about its shape
+    Custom(fn(i64) -> i64),
+}
+
+pub fn fuel_cost(pos: i64, target: i64, mode: CostMode) -> i64 {
+    let distance = (target - pos).abs();
+    match mode {
+        CostMode::Linear => distance,
+        CostMode::Quadratic => distance * (distance + 1) / 2,
+        CostMode::Convex(cost) | CostMode::Custom(cost) => cost(distance),
+    }
+}
+
+pub fn total_fuel_cost(positions: &[i64], target: i64, mode: CostMode) -> i64 {
+    let mut cost = 0;
+    for &pos in positions {
+        cost += fuel_cost(pos, target, mode);
+    }
+    cost
+}
+
+// Cheapest position to align the crabs to, the leftmost one when several cost the same
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Alignment {
+    pub position: i64,
+    pub cost: i64,
+}
+
+// Cheapest of the candidate positions
+fn cheapest(positions: &[i64], candidates: impl Iterator<Item = i64>, mode: CostMode) -> Option<Alignment> {
+    candidates
+        .map(|position| Alignment { position, cost: total_fuel_cost(positions, position, mode) })
+        .reduce(|a1, a2| if a2.cost < a1.cost {a2} else {a1})
+}
+
+// Tries every position between the leftmost and rightmost crabs
+pub fn brute_force(positions: &[i64], mode: CostMode) -> Option<Alignment> {
+    let pmin = *positions.iter().min()?;
+    let pmax = *positions.iter().max()?;
+    cheapest(positions, pmin..=pmax, mode)
+}
+
+pub fn align(positions: &[i64], mode: CostMode) -> Option<Alignment> {
+    if positions.is_empty() {
+        return None;
+    }
+    match mode {
+        // Moving away from the median brings closer fewer crabs than it moves away. With an even number of crabs
+        // every position between the two middle ones costs the same, the lower median is the leftmost of them.
+        CostMode::Linear => {
+            let mut sorted = positions.to_vec();
+            let median = (sorted.len() - 1) / 2;
+            let (_, &mut median, _) = sorted.select_nth_unstable(median);
+            cheapest(positions, std::iter::once(median), mode)
+        }
+        // The cost is half the sum of squared and absolute distances, whose real minimum is within 1/2 of the mean, so
+        // the cheapest position is one of the nearest to the mean
+        CostMode::Quadratic => {
+            let mean = positions.iter().sum::<i64>().div_euclid(positions.len() as i64);
+            cheapest(positions, mean - 1..=mean + 2, mode)
+        }
+        // The total cost is convex as well, so the leftmost cheapest position is the first one from which moving right
+        // does not save anything
+        CostMode::Convex(_) => {
+            let (mut low, mut high) = (*positions.iter().min()?, *positions.iter().max()?);
+            while low < high {
+                let middle = low + (high - low) / 2;
+                if total_fuel_cost(positions, middle + 1, mode) >= total_fuel_cost(positions, middle, mode) {
+                    high = middle;
+                } else {
+                    low = middle + 1;
+                }
+            }
+            cheapest(positions, std::iter::once(low), mode)
+        }
+        // Nothing to say about the shape of the cost
+        CostMode::Custom(_) => brute_force(positions, mode),
+    }
+}
+
+/* Tests **************************************************************************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [i64; 10] = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+
+    // Crabs scattered by a linear congruential generator, with clusters and outliers
+    fn scattered(n: usize, seed: u64) -> Vec<i64> {
+        let mut state = seed;
+        (0..n).map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let value = (state >> 33) as i64;
+            if value % 7 == 0 { value % 2000 - 1000 } else { value % 50 }
+        }).collect()
+    }
+
+    #[test]
+    fn example() {
+        assert_eq!(align(&EXAMPLE, CostMode::Linear), Some(Alignment { position: 2, cost: 37 }));
+        assert_eq!(align(&EXAMPLE, CostMode::Quadratic), Some(Alignment { position: 5, cost: 168 }));
+        assert_eq!(brute_force(&EXAMPLE, CostMode::Linear), Some(Alignment { position: 2, cost: 37 }));
+        assert_eq!(brute_force(&EXAMPLE, CostMode::Quadratic), Some(Alignment { position: 5, cost: 168 }));
+        assert_eq!(align(&[], CostMode::Linear), None);
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        for (n, seed) in [(1, 1), (2, 2), (5, 3), (10, 4), (101, 5), (500, 6)] {
+            let positions = scattered(n, seed);
+            for mode in [CostMode::Linear, CostMode::Quadratic] {
+                assert_eq!(align(&positions, mode), brute_force(&positions, mode), "{} crabs, seed {}", n, seed);
+            }
+        }
+        // Ties are broken to the left
+        assert_eq!(align(&[0, 10], CostMode::Linear), Some(Alignment { position: 0, cost: 10 }));
+        assert_eq!(align(&[0, 1], CostMode::Quadratic), Some(Alignment { position: 0, cost: 1 }));
+    }
+
+    #[test]
+    fn convex() {
+        let costs: [fn(i64) -> i64; 5] = [
+            |distance| distance,
+            |distance| distance * distance,
+            |distance| distance * distance * distance,
+            |distance| (distance - 5).max(0),
+            |distance| if distance < 3 {0} else {distance.pow(4)},
+        ];
+        for (n, seed) in [(1, 1), (2, 2), (5, 3), (10, 4), (101, 5), (500, 6)] {
+            let positions = scattered(n, seed);
+            for cost in costs {
+                let mode = CostMode::Convex(cost);
+                assert_eq!(align(&positions, mode), brute_force(&positions, mode), "{} crabs, seed {}", n, seed);
+            }
+        }
+        // Ties are broken to the left
+        assert_eq!(align(&[0, 10], CostMode::Convex(|distance| distance)), Some(Alignment { position: 0, cost: 10 }));
+        assert_eq!(align(&EXAMPLE, CostMode::Convex(|distance| distance)), align(&EXAMPLE, CostMode::Linear));
+    }
+
+    #[test]
+    fn custom() {
+        // Far away crabs are cheap to move, so the best is to stay in the largest group
+        let concave = CostMode::Custom(|distance| if distance == 0 {0} else {10 + distance.min(3)});
+        assert_eq!(align(&[0, 0, 5, 5, 5, 20], concave), Some(Alignment { position: 5, cost: 39 }));
+        assert_eq!(align(&EXAMPLE, CostMode::Custom(|distance| distance)), align(&EXAMPLE, CostMode::Linear));
+    }
+}